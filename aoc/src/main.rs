@@ -0,0 +1,81 @@
+use anyhow::{anyhow, Result};
+use clap::{Parser, Subcommand};
+use common::bench::{bench, Bencher};
+use common::Solver;
+
+#[derive(Parser)]
+struct Args {
+    #[command(subcommand)]
+    cmd: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Solve a given day
+    Run {
+        #[arg(long, short)]
+        /// Day to run
+        day: u8,
+        #[arg(long, short, value_parser = clap::value_parser!(u8).range(1..=2))]
+        /// Only print this part
+        part: Option<u8>,
+        #[arg(long, short)]
+        /// Puzzle input
+        input: String,
+        #[arg(long)]
+        /// Time parsing and each part instead of printing answers
+        bench: bool,
+        #[arg(long, short, default_value_t = 10)]
+        /// Number of repetitions in bench mode
+        runs: usize,
+    },
+}
+
+fn entry(day: u8) -> Option<(Solver, Bencher)> {
+    let e: (Solver, Bencher) = match day {
+        1 => (d1::solve, bench::<d1::Day>),
+        2 => (d2::solve, bench::<d2::Day>),
+        3 => (d3::solve, bench::<d3::Day>),
+        4 => (d4::solve, bench::<d4::Day>),
+        5 => (d5::solve, bench::<d5::Day>),
+        6 => (d6::solve, bench::<d6::Day>),
+        8 => (d8::solve, bench::<d8::Day>),
+        _ => return None,
+    };
+
+    Some(e)
+}
+
+fn main() -> Result<()> {
+    let args = Args::parse();
+
+    match args.cmd {
+        Command::Run {
+            day,
+            part,
+            input,
+            bench,
+            runs,
+        } => {
+            let (solve, bencher) = entry(day).ok_or(anyhow!("No solver for day {}", day))?;
+            let data = common::read_input(&input)?;
+
+            if bench {
+                println!("Day {} {}", day, bencher(&data, runs)?);
+                return Ok(());
+            }
+
+            let (p1, p2) = solve(&data)?;
+
+            if part.unwrap_or(1) == 1 {
+                println!("Day {} part 1: {}", day, p1);
+            }
+
+            if part.unwrap_or(2) == 2 {
+                println!("Day {} part 2: {}", day, p2);
+            }
+        }
+    }
+
+    Ok(())
+}
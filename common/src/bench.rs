@@ -0,0 +1,88 @@
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, Result};
+
+use crate::Puzzle;
+
+/// Signature of the per-day timing entry point
+pub type Bencher = fn(&str, usize) -> Result<Timings>;
+
+/// Spread of the durations measured for one phase
+pub struct Stats {
+    pub min: Duration,
+    pub median: Duration,
+    pub max: Duration,
+}
+
+impl Stats {
+    fn new(mut samples: Vec<Duration>) -> Stats {
+        samples.sort();
+        Stats {
+            min: samples[0],
+            median: samples[samples.len() / 2],
+            max: samples[samples.len() - 1],
+        }
+    }
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "min {:>12?} median {:>12?} max {:>12?}",
+            self.min, self.median, self.max
+        )
+    }
+}
+
+pub struct Timings {
+    pub runs: usize,
+    pub parse: Stats,
+    pub part1: Stats,
+    pub part2: Stats,
+}
+
+impl fmt::Display for Timings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{} runs", self.runs)?;
+        writeln!(f, "parse  {}", self.parse)?;
+        writeln!(f, "part 1 {}", self.part1)?;
+        write!(f, "part 2 {}", self.part2)
+    }
+}
+
+fn timed<T>(f: impl FnOnce() -> Result<T>) -> Result<(T, Duration)> {
+    let start = Instant::now();
+    let ret = f()?;
+    Ok((ret, start.elapsed()))
+}
+
+/// Time each phase of a puzzle separately over `runs` repetitions
+pub fn bench<P: Puzzle>(input: &str, runs: usize) -> Result<Timings> {
+    if runs == 0 {
+        return Err(anyhow!("At least one run is needed"));
+    }
+
+    let mut parse = Vec::with_capacity(runs);
+    let mut part1 = Vec::with_capacity(runs);
+    let mut part2 = Vec::with_capacity(runs);
+
+    for _ in 0..runs {
+        let (parsed, d) = timed(|| P::parse(input))?;
+        parse.push(d);
+
+        let (_, d) = timed(|| P::part1(&parsed))?;
+        part1.push(d);
+
+        let (_, d) = timed(|| P::part2(&parsed))?;
+        part2.push(d);
+    }
+
+    Ok(Timings {
+        runs,
+        parse: Stats::new(parse),
+        part1: Stats::new(part1),
+        part2: Stats::new(part2),
+    })
+}
@@ -0,0 +1,66 @@
+use std::path::Path;
+
+use anyhow::{anyhow, Result};
+use serde::Deserialize;
+
+use crate::{read_input, Answer, Solver};
+
+/// Known answers for one input file, relative to `expected.toml`
+#[derive(Deserialize)]
+pub struct Expected {
+    pub file: String,
+    pub part1: Option<Answer>,
+    pub part2: Option<Answer>,
+}
+
+#[derive(Deserialize)]
+struct ExpectedFile {
+    #[serde(default)]
+    input: Vec<Expected>,
+}
+
+/// Check the answers listed in `dir/expected.toml`
+///
+/// Both the toml and the inputs it lists are optional as puzzle inputs are
+/// private, missing ones are skipped. Returns the number of inputs checked.
+pub fn check_expected(dir: &str, solve: Solver) -> Result<usize> {
+    let dir = Path::new(dir);
+    let toml_path = dir.join("expected.toml");
+
+    if !toml_path.exists() {
+        return Ok(0);
+    }
+
+    let expected: ExpectedFile = toml::from_str(&std::fs::read_to_string(&toml_path)?)?;
+
+    let mut checked = 0;
+
+    for e in expected.input {
+        let path = dir.join(&e.file);
+
+        if !path.exists() {
+            continue;
+        }
+
+        let data = read_input(&path.to_string_lossy())?;
+        let (p1, p2) = solve(&data)?;
+
+        for (part, want, got) in [(1, e.part1, p1), (2, e.part2, p2)] {
+            if let Some(want) = want {
+                if want != got {
+                    return Err(anyhow!(
+                        "{} part {}: expected {} got {}",
+                        e.file,
+                        part,
+                        want,
+                        got
+                    ));
+                }
+            }
+        }
+
+        checked += 1;
+    }
+
+    Ok(checked)
+}
@@ -0,0 +1,55 @@
+use std::fs::File;
+use std::io::{stdin, BufRead, BufReader, Read};
+
+use anyhow::Result;
+
+pub mod bench;
+pub mod golden;
+
+/// Value produced by a puzzle part
+pub type Answer = u64;
+
+/// Signature shared by every day entry point
+pub type Solver = fn(&str) -> Result<(Answer, Answer)>;
+
+/// A day split in its parsing and solving phases
+pub trait Puzzle {
+    type Input;
+
+    fn parse(input: &str) -> Result<Self::Input>;
+    fn part1(input: &Self::Input) -> Result<Answer>;
+    fn part2(input: &Self::Input) -> Result<Answer>;
+}
+
+/// Parse then run both parts of a puzzle
+pub fn solve<P: Puzzle>(input: &str) -> Result<(Answer, Answer)> {
+    let parsed = P::parse(input)?;
+    Ok((P::part1(&parsed)?, P::part2(&parsed)?))
+}
+
+/// Open a puzzle input for streaming, `-` being stdin
+pub fn open_input(path: &str) -> Result<Box<dyn BufRead>> {
+    if path == "-" {
+        return Ok(Box::new(BufReader::new(stdin())));
+    }
+
+    let f = File::open(path)?;
+    Ok(Box::new(BufReader::new(f)))
+}
+
+/// Read a whole puzzle input in memory, `-` being stdin
+pub fn read_input(path: &str) -> Result<String> {
+    let mut reader = open_input(path)?;
+    let mut data = String::new();
+    reader.read_to_string(&mut data)?;
+    Ok(data)
+}
+
+/// Seeded pseudo-random numbers of 31 bits, shared by tests and benches
+#[doc(hidden)]
+pub fn lcg(mut seed: u64) -> impl FnMut() -> u64 {
+    move || {
+        seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1);
+        seed >> 33
+    }
+}
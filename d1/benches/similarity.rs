@@ -0,0 +1,20 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use d1::{parse, similarity, Algo};
+
+fn similarity_algos(c: &mut Criterion) {
+    let mut rand = common::lcg(42);
+    let mut next = || 10000 + rand() % 90000;
+    let data: String = (0..1000)
+        .map(|_| format!("{}   {}\n", next(), next()))
+        .collect();
+    let l = parse(&data, true).unwrap();
+
+    for algo in [Algo::Naive, Algo::Hashmap, Algo::SortedMerge] {
+        c.bench_function(&format!("similarity {:?}", algo), |bch| {
+            bch.iter(|| similarity(black_box(&l.a), black_box(&l.b), algo))
+        });
+    }
+}
+
+criterion_group!(benches, similarity_algos);
+criterion_main!(benches);
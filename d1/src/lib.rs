@@ -0,0 +1,234 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::fmt;
+
+use anyhow::{anyhow, Result};
+use clap::ValueEnum;
+use common::{Answer, Puzzle};
+
+/// Line which could not be read as two numbers
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Malformed {
+    pub line: usize,
+    pub reason: String,
+}
+
+impl fmt::Display for Malformed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.reason)
+    }
+}
+
+/// Both columns sorted, with the lines that were skipped
+pub struct Lists {
+    pub a: Vec<u64>,
+    pub b: Vec<u64>,
+    pub malformed: Vec<Malformed>,
+}
+
+/// Split a line on commas if any (CSV), on any whitespace otherwise
+fn fields(l: &str) -> Vec<&str> {
+    if l.contains(',') {
+        l.split(',').map(|v| v.trim()).collect()
+    } else {
+        l.split_whitespace().collect()
+    }
+}
+
+fn parse_line(l: &str, n: Option<usize>) -> Result<Vec<u64>, String> {
+    let vals = fields(l)
+        .iter()
+        .map(|v| v.parse::<u64>().map_err(|e| format!("'{}' {}", v, e)))
+        .collect::<Result<Vec<u64>, String>>()?;
+
+    match n {
+        Some(n) if vals.len() != n => Err(format!("expected {} values got {}", n, vals.len())),
+        None if vals.len() < 2 => Err(format!("expected columns got {} value", vals.len())),
+        _ => Ok(vals),
+    }
+}
+
+/// Sorted columns with the lines that were skipped
+pub struct Columns {
+    pub cols: Vec<Vec<u64>>,
+    pub malformed: Vec<Malformed>,
+}
+
+/// Read `n` columns, or as many as on the first valid line when `None`
+///
+/// Blank lines are ignored. Malformed lines are skipped and listed unless
+/// `strict` where the first one is an error.
+pub fn parse_columns(data: &str, n: Option<usize>, strict: bool) -> Result<Columns> {
+    let mut n = n;
+    let mut cols: Vec<Vec<u64>> = vec![Vec::new(); n.unwrap_or(0)];
+    let mut malformed: Vec<Malformed> = Vec::new();
+
+    for (i, l) in data.lines().enumerate() {
+        if l.trim().is_empty() {
+            continue;
+        }
+
+        match parse_line(l, n) {
+            Ok(vals) => {
+                if n.is_none() {
+                    n = Some(vals.len());
+                    cols = vec![Vec::new(); vals.len()];
+                }
+
+                for (c, v) in cols.iter_mut().zip(vals) {
+                    c.push(v);
+                }
+            }
+            Err(reason) => {
+                let m = Malformed {
+                    line: i + 1,
+                    reason,
+                };
+                if strict {
+                    return Err(anyhow!("{}", m));
+                }
+                malformed.push(m);
+            }
+        }
+    }
+
+    for c in cols.iter_mut() {
+        c.sort();
+    }
+
+    Ok(Columns { cols, malformed })
+}
+
+/// Read two columns, see `parse_columns`
+pub fn parse(data: &str, strict: bool) -> Result<Lists> {
+    /* Vecteurs A et B pour chaque colonne
+     *  Calcul du diff
+     * Somme
+     */
+    let Columns { cols, malformed } = parse_columns(data, Some(2), strict)?;
+    let [a, b]: [Vec<u64>; 2] = cols.try_into().unwrap();
+
+    Ok(Lists { a, b, malformed })
+}
+
+/// How two sorted columns are compared, element by element
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Metric {
+    /// Sum of absolute differences (part 1)
+    L1,
+    /// Euclidean distance
+    L2,
+    /// Largest absolute difference
+    Max,
+    /// Number of positions holding different values
+    Mismatches,
+}
+
+pub fn metric_distance(a: &[u64], b: &[u64], metric: Metric) -> f64 {
+    let diffs = a.iter().zip(b.iter()).map(|(va, vb)| va.abs_diff(*vb));
+
+    match metric {
+        Metric::L1 => diffs.sum::<u64>() as f64,
+        Metric::L2 => diffs.map(|d| (d as f64) * (d as f64)).sum::<f64>().sqrt(),
+        Metric::Max => diffs.max().unwrap_or(0) as f64,
+        Metric::Mismatches => diffs.filter(|d| *d != 0).count() as f64,
+    }
+}
+
+/// Distance between every pair of columns
+pub fn distance_matrix(cols: &[Vec<u64>], metric: Metric) -> Vec<Vec<f64>> {
+    cols.iter()
+        .map(|a| cols.iter().map(|b| metric_distance(a, b, metric)).collect())
+        .collect()
+}
+
+pub fn distance(a: &[u64], b: &[u64]) -> u64 {
+    a.iter()
+        .zip(b.iter())
+        .map(|(va, vb)| va.abs_diff(*vb))
+        .sum()
+}
+
+/// How the part 2 similarity score is computed
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Algo {
+    /// Count occurrences with a scan of `b` per value, O(n²)
+    Naive,
+    /// Count occurrences of `b` in a map, O(n)
+    Hashmap,
+    /// Walk both sorted lists together, O(n)
+    SortedMerge,
+}
+
+fn similarity_naive(a: &[u64], b: &[u64]) -> u64 {
+    a.iter()
+        .map(|va| {
+            let occ = b.iter().filter(|vb| *va == **vb).count();
+            *va * occ as u64
+        })
+        .sum()
+}
+
+fn similarity_hashmap(a: &[u64], b: &[u64]) -> u64 {
+    let mut m: HashMap<u64, u64> = HashMap::new();
+
+    for v1 in b.iter() {
+        *m.entry(*v1).or_insert(0) += 1;
+    }
+
+    a.iter().map(|v1| v1 * m.get(v1).unwrap_or(&0)).sum()
+}
+
+/// Both lists must be sorted, as returned by `parse`
+fn similarity_sorted_merge(a: &[u64], b: &[u64]) -> u64 {
+    let mut sum = 0;
+    let (mut i, mut j) = (0, 0);
+
+    while (i < a.len()) && (j < b.len()) {
+        match a[i].cmp(&b[j]) {
+            Ordering::Less => i += 1,
+            Ordering::Greater => j += 1,
+            Ordering::Equal => {
+                let v = a[i];
+                let ca = a[i..].iter().take_while(|x| **x == v).count();
+                let cb = b[j..].iter().take_while(|x| **x == v).count();
+                sum += v * (ca * cb) as u64;
+                i += ca;
+                j += cb;
+            }
+        }
+    }
+
+    sum
+}
+
+/// Sum of each value of `a` times its occurrences in `b`
+pub fn similarity(a: &[u64], b: &[u64], algo: Algo) -> u64 {
+    match algo {
+        Algo::Naive => similarity_naive(a, b),
+        Algo::Hashmap => similarity_hashmap(a, b),
+        Algo::SortedMerge => similarity_sorted_merge(a, b),
+    }
+}
+
+pub struct Day;
+
+impl Puzzle for Day {
+    type Input = Lists;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        parse(input, false)
+    }
+
+    fn part1(l: &Self::Input) -> Result<Answer> {
+        Ok(distance(&l.a, &l.b))
+    }
+
+    fn part2(l: &Self::Input) -> Result<Answer> {
+        Ok(similarity(&l.a, &l.b, Algo::SortedMerge))
+    }
+}
+
+pub fn solve(input: &str) -> Result<(Answer, Answer)> {
+    common::solve::<Day>(input)
+}
@@ -1,87 +1,60 @@
+use anyhow::Result;
 use clap::Parser;
-use std::collections::HashMap;
-use std::fs::File;
-use std::io::{BufReader, Read};
+use d1::{distance, distance_matrix, parse, parse_columns, similarity, Algo, Metric};
 
 #[derive(Parser)]
 struct Args {
-    #[arg(long = "file", short = 'f')]
-    /// Pass a file to parse
+    /// Puzzle input, `-` for stdin
     file: String,
-    #[arg(short, long)]
-    /// Not required
-    lol: Option<String>,
+    #[arg(long, short, value_parser = clap::value_parser!(u8).range(1..=2))]
+    /// Only compute this part
+    part: Option<u8>,
+    #[arg(long, value_enum, default_value_t = Algo::SortedMerge)]
+    /// Similarity score algorithm
+    algo: Algo,
+    #[arg(long)]
+    /// Fail on the first malformed line instead of skipping it
+    strict: bool,
+    #[arg(long, value_enum)]
+    /// Read any number of columns and print their pairwise distances
+    matrix: Option<Metric>,
 }
 
-fn main() {
+fn main() -> Result<()> {
     let args = Args::parse();
+    let data = common::read_input(&args.file)?;
 
-    let file = File::open(args.file).unwrap();
-    let mut reader = BufReader::new(file);
-    let mut data = String::new();
-    reader.read_to_string(&mut data).unwrap();
+    if let Some(metric) = args.matrix {
+        let columns = parse_columns(&data, None, args.strict)?;
 
-    /* Vecteurs A et B pour chaque colonne
-     *  Calcul du diff
-     * Somme
-     */
-    let mut a: Vec<u64> = Vec::new();
-    let mut b: Vec<u64> = Vec::new();
-
-    for l in data.lines() {
-        let vals: Vec<u64> = l.split("   ").map(|v| v.parse().unwrap()).collect();
-
-        if vals.len() != 2 {
-            continue;
+        for m in columns.malformed.iter() {
+            eprintln!("Skipping {}", m);
         }
-        a.push(vals[0]);
-        b.push(vals[1]);
-    }
-
-    a.sort();
-    b.sort();
-
-    let sum: u64 = a
-        .iter()
-        .zip(b.iter())
-        .map(|(va, vb)| va.abs_diff(*vb))
-        .sum();
 
-    println!("Q1 {}", sum);
+        let header: Vec<String> = (0..columns.cols.len()).map(|i| i.to_string()).collect();
+        println!("\t{}", header.join("\t"));
 
-    let mut sum: u64 = 0;
-
-    for v1 in a.iter() {
-        let mut occ = 0;
-        for v2 in b.iter() {
-            if v2 == v1 {
-                occ += 1;
-            }
+        for (i, row) in distance_matrix(&columns.cols, metric).iter().enumerate() {
+            let row: Vec<String> = row.iter().map(|v| v.to_string()).collect();
+            println!("{}\t{}", i, row.join("\t"));
         }
-        sum += occ * v1;
+
+        return Ok(());
     }
 
-    println!("Q2' {}", sum);
+    let lists = parse(&data, args.strict)?;
 
-    let sum: u64 = a
-        .iter()
-        .map(|va| {
-            let occ = b.iter().filter(|vb| *va == **vb).count();
-            *va * occ as u64
-        })
-        .sum();
-    println!("Q2 {}", sum);
+    for m in lists.malformed.iter() {
+        eprintln!("Skipping {}", m);
+    }
 
-    let mut m: HashMap<u64, u64> = HashMap::new();
+    if args.part.unwrap_or(1) == 1 {
+        println!("Q1 {}", distance(&lists.a, &lists.b));
+    }
 
-    for v1 in b.iter() {
-        if let Some(k) = m.get_mut(v1) {
-            *k += 1;
-        } else {
-            m.insert(*v1, 1);
-        }
+    if args.part.unwrap_or(2) == 2 {
+        println!("Q2 {}", similarity(&lists.a, &lists.b, args.algo));
     }
 
-    let tot: u64 = a.iter().map(|v1| v1 * m.get(v1).unwrap_or(&0)).sum();
-    println!("Q2'' {}", tot);
+    Ok(())
 }
@@ -0,0 +1,89 @@
+use d1::{distance_matrix, parse, parse_columns, similarity, solve, Algo, Metric};
+
+#[test]
+fn sample() {
+    let (p1, p2) = solve(include_str!("../sample.txt")).unwrap();
+    assert_eq!(p1, 11);
+    assert_eq!(p2, 31);
+}
+
+#[test]
+fn expected() {
+    common::golden::check_expected(env!("CARGO_MANIFEST_DIR"), solve).unwrap();
+}
+
+#[test]
+fn similarity_algos() {
+    /* Deterministic pseudo-random lists with many repeats */
+    let mut rand = common::lcg(42);
+    let mut next = || rand() % 50;
+    let data: String = (0..500)
+        .map(|_| format!("{}   {}\n", next(), next()))
+        .collect();
+
+    for input in [include_str!("../sample.txt"), data.as_str()] {
+        let l = parse(input, true).unwrap();
+        let (a, b) = (&l.a, &l.b);
+        let naive = similarity(a, b, Algo::Naive);
+        assert_eq!(similarity(a, b, Algo::Hashmap), naive);
+        assert_eq!(similarity(a, b, Algo::SortedMerge), naive);
+    }
+}
+
+#[test]
+fn delimiters() {
+    let expected = parse(include_str!("../sample.txt"), true).unwrap();
+
+    for input in [
+        "3\t4\n4\t3\n2\t5\n1\t3\n3\t9\n3\t3\n",
+        "3 4\n4 3\n\n2 5\n1  3\n3 9\n3 3\n",
+        "3,4\n4, 3\n2 ,5\n1,3\n3,9\n3,3\n",
+    ] {
+        let l = parse(input, true).unwrap();
+        assert_eq!((l.a, l.b), (expected.a.clone(), expected.b.clone()));
+    }
+}
+
+#[test]
+fn malformed_lines() {
+    let input = "1   2\n3\n4 x\n5 6 7\n8   9\n";
+
+    let l = parse(input, false).unwrap();
+    assert_eq!((l.a, l.b), (vec![1, 8], vec![2, 9]));
+    assert_eq!(
+        l.malformed.iter().map(|m| m.line).collect::<Vec<usize>>(),
+        vec![2, 3, 4]
+    );
+
+    let e = parse(input, true).err().unwrap();
+    assert_eq!(e.to_string(), "line 2: expected 2 values got 1");
+}
+
+#[test]
+fn columns_matrix() {
+    let input = "1 4 1\n2 2 2\n3 9 3\n";
+
+    let c = parse_columns(input, None, true).unwrap();
+    assert_eq!(c.cols, vec![vec![1, 2, 3], vec![2, 4, 9], vec![1, 2, 3]]);
+
+    /* Sorted columns are compared: (1,2) (2,4) (3,9) */
+    assert_eq!(
+        distance_matrix(&c.cols, Metric::L1),
+        vec![
+            vec![0.0, 9.0, 0.0],
+            vec![9.0, 0.0, 9.0],
+            vec![0.0, 9.0, 0.0]
+        ]
+    );
+    assert_eq!(distance_matrix(&c.cols, Metric::L2)[0][1], 41f64.sqrt());
+    assert_eq!(distance_matrix(&c.cols, Metric::Max)[1][2], 6.0);
+    assert_eq!(distance_matrix(&c.cols, Metric::Mismatches)[0][1], 3.0);
+
+    let c = parse_columns("1 2 3\n4 5\n", None, false).unwrap();
+    assert_eq!(c.cols.len(), 3);
+    assert_eq!(c.malformed[0].line, 2);
+
+    /* Two columns L1 is the part 1 answer */
+    let l = parse_columns(include_str!("../sample.txt"), None, true).unwrap();
+    assert_eq!(distance_matrix(&l.cols, Metric::L1)[0][1], 11.0);
+}
@@ -0,0 +1,208 @@
+use std::io::Write;
+
+use anyhow::Result;
+use clap::ValueEnum;
+use serde::Serialize;
+
+use crate::{Direction, Report, SafetyPolicy};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum DiagFormat {
+    /// One JSON object per report
+    Jsonl,
+    Csv,
+}
+
+/// Overall direction of a report, given by its first non flat step
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Trend {
+    Increasing,
+    Decreasing,
+    Flat,
+}
+
+/// Why a pair of neighbouring levels is not a valid step
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Reason {
+    TooLarge,
+    TooSmall,
+    Flat,
+    /// The pair goes against the previous steps
+    DirectionChange,
+    /// The pair goes against the direction required by the policy
+    WrongDirection,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub struct Violation {
+    /// Index of the left level of the pair
+    pub pos: usize,
+    pub before: u64,
+    pub after: u64,
+    pub reason: Reason,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Diagnosis {
+    pub line: usize,
+    pub direction: Trend,
+    pub safe: bool,
+    /// First invalid pair, `None` for safe reports
+    pub violation: Option<Violation>,
+    /// Levels whose removal makes the report safe, empty when there is no
+    /// need or it takes too many. Up to `max_removals` levels, or a single
+    /// one as the puzzle dampener when the policy allows none.
+    pub fixed_by: Vec<usize>,
+}
+
+impl Reason {
+    fn name(&self) -> &'static str {
+        match self {
+            Reason::TooLarge => "too_large",
+            Reason::TooSmall => "too_small",
+            Reason::Flat => "flat",
+            Reason::DirectionChange => "direction_change",
+            Reason::WrongDirection => "wrong_direction",
+        }
+    }
+}
+
+impl Trend {
+    fn name(&self) -> &'static str {
+        match self {
+            Trend::Increasing => "increasing",
+            Trend::Decreasing => "decreasing",
+            Trend::Flat => "flat",
+        }
+    }
+}
+
+impl Report {
+    fn trend(&self) -> Trend {
+        self.vals
+            .windows(2)
+            .find(|w| w[0] != w[1])
+            .map(|w| {
+                if w[1] > w[0] {
+                    Trend::Increasing
+                } else {
+                    Trend::Decreasing
+                }
+            })
+            .unwrap_or(Trend::Flat)
+    }
+
+    /// Explain why a report is safe or not under `policy`
+    ///
+    /// The first invalid pair is looked for in the policy direction when
+    /// there is one, otherwise in the direction of its first non flat step.
+    /// The verdict accounts for `max_removals` as `Report::safe_with`.
+    pub fn diagnose(&self, line: usize, policy: &SafetyPolicy) -> Diagnosis {
+        let direction = self.trend();
+
+        let increasing = match policy.direction {
+            Direction::Increasing => true,
+            Direction::Decreasing => false,
+            Direction::Either => direction != Trend::Decreasing,
+        };
+
+        let violation = self
+            .vals
+            .windows(2)
+            .enumerate()
+            .find(|(_, w)| !policy.step_ok(increasing, w[0], w[1]))
+            .map(|(pos, w)| {
+                let (before, after) = (w[0], w[1]);
+                let d = before.abs_diff(after);
+
+                let reason = if d == 0 {
+                    Reason::Flat
+                } else if (after > before) != increasing {
+                    if policy.direction == Direction::Either {
+                        Reason::DirectionChange
+                    } else {
+                        Reason::WrongDirection
+                    }
+                } else if d > policy.max_step {
+                    Reason::TooLarge
+                } else {
+                    Reason::TooSmall
+                };
+
+                Violation {
+                    pos,
+                    before,
+                    after,
+                    reason,
+                }
+            });
+
+        let fixed_by = match (violation, policy.max_removals) {
+            (None, _) => Vec::new(),
+            (Some(_), 0 | 1) => self.dampen_with(policy).into_iter().collect(),
+            (Some(_), k) => {
+                let r = self.removals(policy);
+                if r.len() <= k {
+                    r
+                } else {
+                    Vec::new()
+                }
+            }
+        };
+
+        Diagnosis {
+            line,
+            direction,
+            safe: self.safe_with(policy),
+            violation,
+            fixed_by,
+        }
+    }
+}
+
+fn opt<T: ToString>(v: Option<T>) -> String {
+    v.map(|v| v.to_string()).unwrap_or_default()
+}
+
+/// Write a diagnosis for every report, along with its line number
+pub fn write_diagnostics<W: Write>(
+    reports: &[(usize, Report)],
+    policy: &SafetyPolicy,
+    format: DiagFormat,
+    mut out: W,
+) -> Result<()> {
+    if format == DiagFormat::Csv {
+        writeln!(out, "line,direction,safe,pos,before,after,reason,fixed_by")?;
+    }
+
+    for (line, r) in reports.iter() {
+        let d = r.diagnose(*line, policy);
+
+        match format {
+            DiagFormat::Jsonl => writeln!(out, "{}", serde_json::to_string(&d)?)?,
+            DiagFormat::Csv => {
+                let v = d.violation;
+                writeln!(
+                    out,
+                    "{},{},{},{},{},{},{},{}",
+                    d.line,
+                    d.direction.name(),
+                    d.safe,
+                    opt(v.map(|v| v.pos)),
+                    opt(v.map(|v| v.before)),
+                    opt(v.map(|v| v.after)),
+                    opt(v.map(|v| v.reason.name())),
+                    d.fixed_by
+                        .iter()
+                        .map(|i| i.to_string())
+                        .collect::<Vec<_>>()
+                        .join(";")
+                )?
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,361 @@
+use std::fmt;
+use std::str::FromStr;
+
+use anyhow::{anyhow, Result};
+use clap::ValueEnum;
+use common::{Answer, Puzzle};
+
+mod diagnose;
+
+pub use diagnose::{write_diagnostics, DiagFormat, Diagnosis, Reason, Trend, Violation};
+
+/// Direction the levels of a safe report must follow
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Direction {
+    Increasing,
+    Decreasing,
+    /// Either one, but the same for the whole report
+    Either,
+}
+
+impl Direction {
+    /// Candidate directions as `increasing` flags
+    fn candidates(&self) -> &'static [bool] {
+        match self {
+            Direction::Increasing => &[true],
+            Direction::Decreasing => &[false],
+            Direction::Either => &[true, false],
+        }
+    }
+}
+
+/// What makes a report safe
+///
+/// Steps between neighbours must be within `min_step..=max_step`, equal
+/// neighbours being only accepted with `allow_flat`. A report is also safe
+/// if it becomes so after dropping up to `max_removals` levels.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SafetyPolicy {
+    pub min_step: u64,
+    pub max_step: u64,
+    pub allow_flat: bool,
+    pub direction: Direction,
+    pub max_removals: usize,
+}
+
+impl Default for SafetyPolicy {
+    /// Rules of the puzzle, without the dampener
+    fn default() -> Self {
+        SafetyPolicy {
+            min_step: 1,
+            max_step: 3,
+            allow_flat: false,
+            direction: Direction::Either,
+            max_removals: 0,
+        }
+    }
+}
+
+impl SafetyPolicy {
+    pub fn validate(&self) -> Result<()> {
+        if self.min_step > self.max_step {
+            return Err(anyhow!(
+                "Minimum step {} is above maximum step {}",
+                self.min_step,
+                self.max_step
+            ));
+        }
+        Ok(())
+    }
+
+    fn step_ok(&self, increasing: bool, v1: u64, v2: u64) -> bool {
+        if v1 == v2 {
+            return self.allow_flat;
+        }
+
+        if (v2 > v1) != increasing {
+            return false;
+        }
+
+        let d = v1.abs_diff(v2);
+        (self.min_step <= d) && (d <= self.max_step)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ParseErrorKind {
+    /// Blank line
+    Empty,
+    /// Two spaces in a row, or a leading or trailing one
+    EmptyLevel,
+    /// Not a number, or too large
+    InvalidLevel,
+}
+
+impl fmt::Display for ParseErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseErrorKind::Empty => write!(f, "empty report"),
+            ParseErrorKind::EmptyLevel => write!(f, "empty level"),
+            ParseErrorKind::InvalidLevel => write!(f, "invalid level"),
+        }
+    }
+}
+
+/// Report which could not be read, `column` counts chars from 1
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub kind: ParseErrorKind,
+    /// Offending level as read
+    pub token: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: {}",
+            self.line, self.column, self.kind
+        )?;
+        if !self.token.is_empty() {
+            write!(f, " '{}'", self.token)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Report {
+    vals: Vec<u64>,
+}
+
+/// A string is read as line 1, see `Report::parse_line`
+impl FromStr for Report {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Report::parse_line(s, 1)
+    }
+}
+
+impl Report {
+    /// Levels separated by single spaces
+    pub fn parse_line(data: &str, line: usize) -> Result<Report, ParseError> {
+        let err = |offset: usize, kind, token: &str| ParseError {
+            line,
+            column: data[..offset].chars().count() + 1,
+            kind,
+            token: token.to_string(),
+        };
+
+        if data.trim().is_empty() {
+            return Err(err(0, ParseErrorKind::Empty, ""));
+        }
+
+        let mut vals = Vec::new();
+        let mut offset = 0;
+
+        for v in data.split(' ') {
+            if v.is_empty() {
+                return Err(err(offset, ParseErrorKind::EmptyLevel, ""));
+            }
+
+            vals.push(
+                v.parse()
+                    .map_err(|_| err(offset, ParseErrorKind::InvalidLevel, v))?,
+            );
+            offset += v.len() + 1;
+        }
+
+        Ok(Report { vals })
+    }
+
+    pub fn levels(&self) -> &[u64] {
+        &self.vals
+    }
+
+    pub fn safe(&self) -> bool {
+        self.safe_with(&SafetyPolicy::default())
+    }
+
+    pub fn safe_minus_one(&self) -> bool {
+        self.safe_with(&SafetyPolicy {
+            max_removals: 1,
+            ..SafetyPolicy::default()
+        })
+    }
+
+    /// Check the report against a policy, removals included
+    pub fn safe_with(&self, policy: &SafetyPolicy) -> bool {
+        let strict = SafetyPolicy {
+            max_removals: 0,
+            ..*policy
+        };
+
+        match policy.max_removals {
+            0 => policy
+                .direction
+                .candidates()
+                .iter()
+                .any(|inc| self.first_bad(policy, *inc, None).is_none()),
+            1 => self.safe_with(&strict) || self.dampen_with(policy).is_some(),
+            k => self.min_removals(policy) <= k,
+        }
+    }
+
+    /// Left index of the first pair which is not a valid step in the given
+    /// direction, the `skip` level being left out
+    fn first_bad(
+        &self,
+        policy: &SafetyPolicy,
+        increasing: bool,
+        skip: Option<usize>,
+    ) -> Option<usize> {
+        let mut prev: Option<usize> = None;
+
+        for (i, v2) in self.vals.iter().enumerate() {
+            if Some(i) == skip {
+                continue;
+            }
+
+            if let Some(p) = prev {
+                if !policy.step_ok(increasing, self.vals[p], *v2) {
+                    return Some(p);
+                }
+            }
+
+            prev = Some(i);
+        }
+
+        None
+    }
+
+    /// Index of the level to remove to make an unsafe report safe
+    pub fn dampen(&self) -> Option<usize> {
+        self.dampen_with(&SafetyPolicy::default())
+    }
+
+    /// Index of the level to remove to make a report safe under `policy`
+    ///
+    /// Whichever the direction, the removed level has to be one of the first
+    /// bad pair so only two candidates are checked, in O(n) overall. Safe
+    /// reports need no removal and give `None`, `max_removals` is ignored.
+    pub fn dampen_with(&self, policy: &SafetyPolicy) -> Option<usize> {
+        let dirs = policy.direction.candidates();
+
+        if dirs
+            .iter()
+            .any(|inc| self.first_bad(policy, *inc, None).is_none())
+        {
+            return None;
+        }
+
+        for increasing in dirs {
+            if let Some(k) = self.first_bad(policy, *increasing, None) {
+                for candi in [k, k + 1] {
+                    if self.first_bad(policy, *increasing, Some(candi)).is_none() {
+                        return Some(candi);
+                    }
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Fewest levels to drop for the report to be safe under `policy`
+    pub fn min_removals(&self, policy: &SafetyPolicy) -> usize {
+        self.removals(policy).len()
+    }
+
+    /// Indices of the fewest levels to drop for the report to be safe under
+    /// `policy`, `max_removals` is ignored
+    ///
+    /// Longest chain of levels with valid steps between them, in O(n²).
+    pub fn removals(&self, policy: &SafetyPolicy) -> Vec<usize> {
+        let n = self.vals.len();
+        let mut ret: Option<Vec<usize>> = None;
+
+        for inc in policy.direction.candidates() {
+            let mut best: Vec<usize> = vec![1; n];
+            let mut prev: Vec<Option<usize>> = vec![None; n];
+
+            for j in 0..n {
+                for i in 0..j {
+                    if policy.step_ok(*inc, self.vals[i], self.vals[j]) && (best[i] + 1 > best[j]) {
+                        best[j] = best[i] + 1;
+                        prev[j] = Some(i);
+                    }
+                }
+            }
+
+            let mut kept = vec![false; n];
+            let mut cur = (0..n).max_by_key(|j| best[*j]);
+
+            while let Some(j) = cur {
+                kept[j] = true;
+                cur = prev[j];
+            }
+
+            let dropped: Vec<usize> = (0..n).filter(|i| !kept[*i]).collect();
+
+            if ret.as_ref().is_none_or(|r| dropped.len() < r.len()) {
+                ret = Some(dropped);
+            }
+        }
+
+        ret.unwrap_or_default()
+    }
+}
+
+/// Reports with their line number, and the lines which were skipped
+pub struct Reports {
+    pub reports: Vec<(usize, Report)>,
+    pub rejected: Vec<ParseError>,
+}
+
+/// Read one report per line
+///
+/// Lines which cannot be read are skipped and listed unless `strict` where
+/// the first one is an error.
+pub fn parse_reports(input: &str, strict: bool) -> Result<Reports> {
+    let mut reports = Vec::new();
+    let mut rejected = Vec::new();
+
+    for (n, l) in input.lines().enumerate() {
+        match Report::parse_line(l, n + 1) {
+            Ok(r) => reports.push((n + 1, r)),
+            Err(e) if strict => return Err(anyhow!("{}", e)),
+            Err(e) => rejected.push(e),
+        }
+    }
+
+    Ok(Reports { reports, rejected })
+}
+
+pub struct Day;
+
+impl Puzzle for Day {
+    type Input = Vec<Report>;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        let reports = parse_reports(input, true)?;
+        Ok(reports.reports.into_iter().map(|(_, r)| r).collect())
+    }
+
+    fn part1(reports: &Self::Input) -> Result<Answer> {
+        Ok(reports.iter().filter(|v| v.safe()).count() as Answer)
+    }
+
+    fn part2(reports: &Self::Input) -> Result<Answer> {
+        Ok(reports.iter().filter(|v| v.safe_minus_one()).count() as Answer)
+    }
+}
+
+pub fn solve(input: &str) -> Result<(Answer, Answer)> {
+    common::solve::<Day>(input)
+}
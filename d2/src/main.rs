@@ -1,85 +1,112 @@
-use std::cmp::Ordering;
-use std::fs::File;
-use std::io::{BufReader, Read};
+use std::collections::BTreeMap;
 
 use anyhow::Result;
 use clap::Parser;
+use common::Puzzle;
+use d2::{DiagFormat, Direction, Reports, SafetyPolicy};
 
 #[derive(Parser)]
 struct Args {
     file: String,
+    #[arg(long)]
+    /// Print unsafe reports with the level the dampener removes
+    culprits: bool,
+    #[arg(long, default_value_t = 1)]
+    /// Smallest step between two levels
+    min_step: u64,
+    #[arg(long, default_value_t = 3)]
+    /// Largest step between two levels
+    max_step: u64,
+    #[arg(long)]
+    /// Accept equal neighbouring levels
+    allow_flat: bool,
+    #[arg(long, value_enum, default_value_t = Direction::Either)]
+    /// Direction the levels must follow
+    direction: Direction,
+    #[arg(long, default_value_t = 0)]
+    /// Levels which may be dropped to make a report safe
+    max_removals: usize,
+    #[arg(long, value_enum)]
+    /// Print a diagnosis of every report instead of the counts
+    diagnostics: Option<DiagFormat>,
+    #[arg(long)]
+    /// Skip reports which cannot be read instead of failing on the first one
+    skip_invalid: bool,
 }
 
-struct Report {
-    vals: Vec<u64>,
-}
+fn main() -> Result<()> {
+    let args = Args::parse();
+    let data = common::read_input(&args.file)?;
 
-impl Report {
-    fn new(data: &str) -> Report {
-        let vals: Vec<u64> = data.split(" ").map(|v| v.parse().unwrap()).collect();
-        Report { vals }
-    }
+    let policy = SafetyPolicy {
+        min_step: args.min_step,
+        max_step: args.max_step,
+        allow_flat: args.allow_flat,
+        direction: args.direction,
+        max_removals: args.max_removals,
+    };
+    policy.validate()?;
 
-    fn safe(&self) -> bool {
-        let mut increasing: Option<Ordering> = None;
+    let Reports { reports, rejected } = d2::parse_reports(&data, !args.skip_invalid)?;
 
-        for (v1, v2) in self.vals.iter().zip(self.vals.iter().skip(1)) {
-            /* Check ORDER */
-            let cmp = v1.cmp(v2);
+    if !rejected.is_empty() {
+        let mut why: BTreeMap<_, usize> = BTreeMap::new();
 
-            if let Some(pcmp) = increasing {
-                if cmp != pcmp {
-                    return false;
-                }
-            } else {
-                increasing = Some(cmp);
-            }
-
-            /* Check increase */
-            if (v1.abs_diff(*v2) > 3) || (v1 == v2) {
-                return false;
-            }
+        for e in rejected.iter() {
+            eprintln!("Skipping {}", e);
+            *why.entry(e.kind).or_default() += 1;
         }
-        true
+
+        let why: Vec<String> = why.iter().map(|(k, n)| format!("{} {}", n, k)).collect();
+        eprintln!("Rejected {} lines: {}", rejected.len(), why.join(", "));
     }
 
-    fn subset(&self, ti: usize) -> Report {
-        let vals = self
-            .vals
-            .iter()
-            .enumerate()
-            .filter_map(|(i, val)| if i == ti { None } else { Some(*val) })
-            .collect();
-        Report { vals }
+    if let Some(format) = args.diagnostics {
+        return d2::write_diagnostics(&reports, &policy, format, std::io::stdout().lock());
     }
 
-    fn safe_minus_one(&self) -> bool {
-        for i in 0..self.vals.len() {
-            let candi = self.subset(i);
-            if candi.safe() {
-                return true;
+    if args.culprits {
+        for (n, r) in reports.iter() {
+            if r.safe_with(&SafetyPolicy {
+                max_removals: 0,
+                ..policy
+            }) {
+                continue;
             }
-        }
 
-        false
+            let culprit = r.dampen_with(&policy);
+
+            let levels: Vec<String> = r
+                .levels()
+                .iter()
+                .enumerate()
+                .map(|(i, v)| {
+                    if Some(i) == culprit {
+                        format!("[{}]", v)
+                    } else {
+                        v.to_string()
+                    }
+                })
+                .collect();
+
+            match culprit {
+                Some(i) => println!("{}: {} => safe without level {}", n, levels.join(" "), i),
+                None => println!("{}: {} => unsafe", n, levels.join(" ")),
+            }
+        }
     }
-}
 
-fn main() -> Result<()> {
-    let args = Args::parse();
-    let f = File::open(args.file)?;
-    let mut reader = BufReader::new(f);
-    let mut data = String::new();
-    reader.read_to_string(&mut data)?;
-
-    let reports = data.lines().map(Report::new).collect::<Vec<Report>>();
+    if policy != SafetyPolicy::default() {
+        let safe = reports.iter().filter(|(_, r)| r.safe_with(&policy)).count();
+        println!("{} reports are safe under the given policy", safe);
+        return Ok(());
+    }
 
-    let safe = reports.iter().filter(|v| v.safe()).count();
+    let reports: Vec<_> = reports.into_iter().map(|(_, r)| r).collect();
+    let safe = d2::Day::part1(&reports)?;
+    let safe_dist_1 = d2::Day::part2(&reports)?;
 
     println!("{} reports are safe", safe);
-
-    let safe_dist_1 = reports.iter().filter(|v| v.safe_minus_one()).count();
-
     println!("{} reports are safe D1", safe_dist_1);
 
     Ok(())
@@ -0,0 +1,267 @@
+use d2::{
+    parse_reports, solve, write_diagnostics, DiagFormat, Direction, ParseErrorKind, Reason, Report,
+    Reports, SafetyPolicy, Trend,
+};
+
+#[test]
+fn sample() {
+    let (p1, p2) = solve(include_str!("../sample.txt")).unwrap();
+    assert_eq!(p1, 2);
+    assert_eq!(p2, 4);
+}
+
+fn report(l: &str) -> Report {
+    l.parse().unwrap()
+}
+
+#[test]
+fn expected() {
+    common::golden::check_expected(env!("CARGO_MANIFEST_DIR"), solve).unwrap();
+}
+
+/// Removal of each level in turn, as the dampener used to do
+fn brute_force(r: &Report) -> bool {
+    let vals = r.levels();
+    (0..vals.len()).any(|i| {
+        let sub: Vec<String> = vals
+            .iter()
+            .enumerate()
+            .filter(|(j, _)| *j != i)
+            .map(|(_, v)| v.to_string())
+            .collect();
+        report(&sub.join(" ")).safe()
+    })
+}
+
+#[test]
+fn dampener() {
+    let culprits: Vec<Option<usize>> = include_str!("../sample.txt")
+        .lines()
+        .map(|l| report(l).dampen())
+        .collect();
+    assert_eq!(culprits, vec![None, None, None, Some(1), Some(2), None]);
+
+    let mut rand = common::lcg(7);
+    let mut next = || rand() % 12;
+
+    for _ in 0..2000 {
+        let len = 2 + next() as usize % 6;
+        let l: Vec<String> = (0..len).map(|_| (next() + 1).to_string()).collect();
+        let r = report(&l.join(" "));
+
+        assert_eq!(r.safe_minus_one(), r.safe() || brute_force(&r), "{:?}", l);
+
+        if let Some(i) = r.dampen() {
+            let mut fixed = r.levels().to_vec();
+            fixed.remove(i);
+            let fixed: Vec<String> = fixed.iter().map(|v| v.to_string()).collect();
+            assert!(report(&fixed.join(" ")).safe());
+        }
+    }
+}
+
+#[test]
+fn policies() {
+    let flat = report("1 2 2 5 9");
+    let mut policy = SafetyPolicy::default();
+    assert!(!flat.safe_with(&policy));
+
+    policy.allow_flat = true;
+    policy.max_step = 4;
+    assert!(flat.safe_with(&policy));
+
+    policy.direction = Direction::Decreasing;
+    assert!(!flat.safe_with(&policy));
+    assert_eq!(flat.min_removals(&policy), 3);
+
+    let policy = SafetyPolicy {
+        max_removals: 2,
+        ..SafetyPolicy::default()
+    };
+    let noisy = report("1 2 9 3 0 4 5");
+    assert!(!noisy.safe_minus_one());
+    assert!(noisy.safe_with(&policy));
+    assert_eq!(noisy.min_removals(&policy), 2);
+
+    assert!(SafetyPolicy {
+        min_step: 4,
+        ..SafetyPolicy::default()
+    }
+    .validate()
+    .is_err());
+}
+
+#[test]
+fn removals_match_dampener() {
+    let one = SafetyPolicy {
+        max_removals: 1,
+        ..SafetyPolicy::default()
+    };
+    let mut rand = common::lcg(11);
+    let mut next = || rand() % 10;
+
+    for _ in 0..2000 {
+        let len = 1 + next() as usize % 7;
+        let l: Vec<String> = (0..len).map(|_| next().to_string()).collect();
+        let r = report(&l.join(" "));
+
+        assert_eq!(r.safe_minus_one(), r.min_removals(&one) <= 1, "{:?}", l);
+        assert_eq!(r.safe(), r.min_removals(&one) == 0, "{:?}", l);
+        assert_eq!(
+            r.safe(),
+            r.diagnose(0, &SafetyPolicy::default()).safe,
+            "{:?}",
+            l
+        );
+
+        let two = SafetyPolicy {
+            max_removals: 2,
+            ..SafetyPolicy::default()
+        };
+        let d = r.diagnose(0, &two);
+        assert_eq!(d.safe, r.safe_with(&two), "{:?}", l);
+
+        if !d.fixed_by.is_empty() {
+            let kept: Vec<String> = l
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| !d.fixed_by.contains(i))
+                .map(|(_, v)| v.clone())
+                .collect();
+            assert!(report(&kept.join(" ")).safe(), "{:?}", l);
+        }
+    }
+}
+
+fn reports(input: &str) -> Vec<(usize, Report)> {
+    parse_reports(input, true).unwrap().reports
+}
+
+#[test]
+fn diagnostics() {
+    let policy = SafetyPolicy::default();
+    let reasons: Vec<Option<Reason>> = include_str!("../sample.txt")
+        .lines()
+        .enumerate()
+        .map(|(n, l)| report(l).diagnose(n + 1, &policy))
+        .map(|d| d.violation.map(|v| v.reason))
+        .collect();
+    assert_eq!(
+        reasons,
+        vec![
+            None,
+            Some(Reason::TooLarge),
+            Some(Reason::TooLarge),
+            Some(Reason::DirectionChange),
+            Some(Reason::Flat),
+            None
+        ]
+    );
+
+    let d = report("1 3 2 4 5").diagnose(4, &policy);
+    assert_eq!(d.direction, Trend::Increasing);
+    assert_eq!(d.violation.map(|v| (v.before, v.after)), Some((3, 2)));
+    assert_eq!(d.fixed_by, vec![1]);
+
+    let down = SafetyPolicy {
+        direction: Direction::Decreasing,
+        ..policy
+    };
+    let d = report("1 3 2").diagnose(1, &down);
+    assert_eq!(d.violation.map(|v| v.reason), Some(Reason::WrongDirection));
+
+    let mut csv = Vec::new();
+    write_diagnostics(
+        &reports("7 6 4 2 1\n1 2 7 8 9\n"),
+        &policy,
+        DiagFormat::Csv,
+        &mut csv,
+    )
+    .unwrap();
+    assert_eq!(
+        String::from_utf8(csv).unwrap(),
+        "line,direction,safe,pos,before,after,reason,fixed_by\n\
+         1,decreasing,true,,,,,\n\
+         2,increasing,false,1,2,7,too_large,\n"
+    );
+
+    let two = SafetyPolicy {
+        max_removals: 2,
+        ..policy
+    };
+    let d = report("1 2 7 8 9").diagnose(1, &two);
+    assert!(d.safe);
+    assert_eq!(d.violation.map(|v| v.reason), Some(Reason::TooLarge));
+    assert_eq!(d.fixed_by, vec![0, 1]);
+
+    let mut csv = Vec::new();
+    write_diagnostics(
+        &reports("1 2 7 8 9\n9 1 9 1\n"),
+        &two,
+        DiagFormat::Csv,
+        &mut csv,
+    )
+    .unwrap();
+    assert_eq!(
+        String::from_utf8(csv)
+            .unwrap()
+            .lines()
+            .skip(1)
+            .collect::<Vec<_>>(),
+        vec![
+            "1,increasing,true,1,2,7,too_large,0;1",
+            "2,decreasing,false,0,9,1,too_large,"
+        ]
+    );
+
+    let mut json = Vec::new();
+    write_diagnostics(
+        &reports("8 6 4 4 1\n"),
+        &policy,
+        DiagFormat::Jsonl,
+        &mut json,
+    )
+    .unwrap();
+    assert_eq!(
+        String::from_utf8(json).unwrap(),
+        "{\"line\":1,\"direction\":\"decreasing\",\"safe\":false,\
+         \"violation\":{\"pos\":2,\"before\":4,\"after\":4,\"reason\":\"flat\"},\
+         \"fixed_by\":[2]}\n"
+    );
+}
+
+#[test]
+fn invalid_reports() {
+    let e = "1 2  3".parse::<Report>().unwrap_err();
+    assert_eq!(
+        (e.line, e.column, e.kind),
+        (1, 5, ParseErrorKind::EmptyLevel)
+    );
+
+    let e = "1 2 3 ".parse::<Report>().unwrap_err();
+    assert_eq!((e.column, e.kind), (7, ParseErrorKind::EmptyLevel));
+
+    let e = " 1".parse::<Report>().unwrap_err();
+    assert_eq!((e.column, e.kind), (1, ParseErrorKind::EmptyLevel));
+
+    let e = "12 x4 5".parse::<Report>().unwrap_err();
+    assert_eq!((e.column, e.kind), (4, ParseErrorKind::InvalidLevel));
+    assert_eq!(e.to_string(), "line 1, column 4: invalid level 'x4'");
+
+    let input = "1 2 3\n\n4 5 6 \n7 8 9\n";
+    assert!(parse_reports(input, true).is_err());
+    assert!(solve(input).is_err());
+
+    let Reports { reports, rejected } = parse_reports(input, false).unwrap();
+    assert_eq!(
+        reports.iter().map(|(n, _)| *n).collect::<Vec<_>>(),
+        vec![1, 4]
+    );
+    assert_eq!(
+        rejected
+            .iter()
+            .map(|e| (e.line, e.kind))
+            .collect::<Vec<_>>(),
+        vec![(2, ParseErrorKind::Empty), (3, ParseErrorKind::EmptyLevel)]
+    );
+}
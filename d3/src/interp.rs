@@ -0,0 +1,306 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::{Arg, Call};
+
+/// Evaluate the arguments of a value instruction
+pub type OpFn = fn(&[i64]) -> Result<i64, String>;
+
+#[derive(Clone, Copy)]
+pub enum Opcode {
+    /// Computes a value from `arity` arguments, summed into the total when
+    /// called at the top level
+    Value { arity: usize, f: OpFn },
+    /// Enable the following instructions
+    Do,
+    /// Disable the following instructions
+    Dont,
+    /// Start a block only enabled when its argument is not zero
+    If,
+    /// End the innermost block
+    EndIf,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum IssueKind {
+    Unknown,
+    Arity {
+        expected: usize,
+        got: usize,
+    },
+    /// Control instruction used as an argument
+    NotAValue,
+    /// `endif` without `if`
+    Unmatched,
+    /// Call given as an argument while nesting is off
+    Nested,
+    Failed(String),
+}
+
+/// Call which could not be executed
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Issue {
+    pub offset: usize,
+    pub name: String,
+    pub kind: IssueKind,
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "offset {}: ", self.offset)?;
+        match &self.kind {
+            IssueKind::Unknown => write!(f, "unknown instruction '{}'", self.name),
+            IssueKind::Arity { expected, got } => write!(
+                f,
+                "'{}' expects {} arguments got {}",
+                self.name, expected, got
+            ),
+            IssueKind::NotAValue => write!(f, "'{}' has no value", self.name),
+            IssueKind::Unmatched => write!(f, "'{}' closes no block", self.name),
+            IssueKind::Nested => write!(f, "'{}' has nested calls", self.name),
+            IssueKind::Failed(e) => write!(f, "'{}' failed: {}", self.name, e),
+        }
+    }
+}
+
+pub struct Execution {
+    /// Sum of the enabled top level values
+    pub total: i64,
+    /// Number of instructions run, disabled ones included
+    pub executed: usize,
+    pub issues: Vec<Issue>,
+}
+
+fn binary(args: &[i64], f: fn(i64, i64) -> Option<i64>) -> Result<i64, String> {
+    f(args[0], args[1]).ok_or(format!("{:?} overflows", args))
+}
+
+struct State {
+    total: i64,
+    executed: usize,
+    issues: Vec<Issue>,
+    enabled: bool,
+    blocks: Vec<bool>,
+}
+
+/// Instruction set, names being resolved on the end of the call name
+///
+/// As in the puzzle `xmul(2,4)` is a `mul`, the longest registered name
+/// which ends the call name wins so `endif` is not taken for `if`.
+pub struct Interpreter {
+    ops: HashMap<String, Opcode>,
+    nested: bool,
+}
+
+impl Interpreter {
+    /// `mul`, `do` and `don't` as in the puzzle, giving the same sum
+    pub fn puzzle() -> Interpreter {
+        let mut ret = Interpreter {
+            ops: HashMap::new(),
+            nested: false,
+        };
+
+        ret.register(
+            "mul",
+            Opcode::Value {
+                arity: 2,
+                f: |a| binary(a, i64::checked_mul),
+            },
+        );
+        ret.register("do", Opcode::Do);
+        ret.register("don't", Opcode::Dont);
+
+        ret
+    }
+
+    /// Puzzle instructions with `add`, `sub`, `div` and `if`/`endif` blocks
+    pub fn extended() -> Interpreter {
+        let mut ret = Interpreter::puzzle();
+
+        ret.register(
+            "add",
+            Opcode::Value {
+                arity: 2,
+                f: |a| binary(a, i64::checked_add),
+            },
+        );
+        ret.register(
+            "sub",
+            Opcode::Value {
+                arity: 2,
+                f: |a| binary(a, i64::checked_sub),
+            },
+        );
+        ret.register(
+            "div",
+            Opcode::Value {
+                arity: 2,
+                f: |a| {
+                    if a[1] == 0 {
+                        return Err("division by zero".to_string());
+                    }
+                    binary(a, i64::checked_div)
+                },
+            },
+        );
+        ret.register("if", Opcode::If);
+        ret.register("endif", Opcode::EndIf);
+
+        ret
+    }
+
+    /// Let values be computed from nested calls, as in `mul(2,add(1,2))`
+    pub fn allow_nested(&mut self, on: bool) {
+        self.nested = on;
+    }
+
+    /// Add or replace an instruction
+    pub fn register(&mut self, name: &str, op: Opcode) {
+        self.ops.insert(name.to_string(), op);
+    }
+
+    fn resolve(&self, name: &str) -> Option<Opcode> {
+        name.char_indices()
+            .find_map(|(i, _)| self.ops.get(&name[i..]))
+            .copied()
+    }
+
+    fn issue(c: &Call, kind: IssueKind) -> Issue {
+        Issue {
+            offset: c.offset,
+            name: c.name.clone(),
+            kind,
+        }
+    }
+
+    fn arg(&self, c: &Call, a: &Arg) -> Result<i64, Issue> {
+        match a {
+            Arg::Num(v) => i64::try_from(*v)
+                .map_err(|_| Self::issue(c, IssueKind::Failed(format!("{} overflows", v)))),
+            Arg::Call(n) => self.value(n),
+        }
+    }
+
+    /// Evaluate a value instruction and its nested calls
+    fn value(&self, c: &Call) -> Result<i64, Issue> {
+        let (arity, f) = match self.resolve(&c.name) {
+            Some(Opcode::Value { arity, f }) => (arity, f),
+            Some(_) => return Err(Self::issue(c, IssueKind::NotAValue)),
+            None => return Err(Self::issue(c, IssueKind::Unknown)),
+        };
+
+        if c.args.len() != arity {
+            return Err(Self::issue(
+                c,
+                IssueKind::Arity {
+                    expected: arity,
+                    got: c.args.len(),
+                },
+            ));
+        }
+
+        let args = c
+            .args
+            .iter()
+            .map(|a| self.arg(c, a))
+            .collect::<Result<Vec<i64>, Issue>>()?;
+
+        f(&args).map_err(|e| Self::issue(c, IssueKind::Failed(e)))
+    }
+
+    /// Run one top level call
+    ///
+    /// A call which is not an instruction, because of its name, its number
+    /// of arguments or nested calls while nesting is off, is reported and
+    /// its nested calls are run in its place as the puzzle would.
+    fn exec(&self, c: &Call, st: &mut State) {
+        let op = match self.resolve(&c.name) {
+            None => Err(IssueKind::Unknown),
+            Some(op) => {
+                let expected = match op {
+                    Opcode::Value { arity, .. } => arity,
+                    Opcode::If => 1,
+                    _ => 0,
+                };
+
+                if c.args.len() != expected {
+                    Err(IssueKind::Arity {
+                        expected,
+                        got: c.args.len(),
+                    })
+                } else if !self.nested && c.nested().next().is_some() {
+                    Err(IssueKind::Nested)
+                } else {
+                    Ok(op)
+                }
+            }
+        };
+
+        let op = match op {
+            Ok(op) => op,
+            Err(kind) => {
+                st.issues.push(Self::issue(c, kind));
+                for n in c.nested() {
+                    self.exec(n, st);
+                }
+                return;
+            }
+        };
+
+        let active = st.enabled && st.blocks.iter().all(|b| *b);
+        st.executed += 1;
+
+        match op {
+            Opcode::Value { .. } => match self.value(c) {
+                Ok(v) if active => match st.total.checked_add(v) {
+                    Some(t) => st.total = t,
+                    None => st.issues.push(Self::issue(
+                        c,
+                        IssueKind::Failed("total overflows".to_string()),
+                    )),
+                },
+                Ok(_) => {}
+                Err(e) => st.issues.push(e),
+            },
+            Opcode::Do => st.enabled = true,
+            Opcode::Dont => st.enabled = false,
+            Opcode::If => match self.arg(c, &c.args[0]) {
+                Ok(v) => st.blocks.push(v != 0),
+                Err(e) => {
+                    st.issues.push(e);
+                    st.blocks.push(false);
+                }
+            },
+            Opcode::EndIf => {
+                if st.blocks.pop().is_none() {
+                    st.issues.push(Self::issue(c, IssueKind::Unmatched));
+                }
+            }
+        }
+    }
+
+    /// Run the calls in order
+    ///
+    /// Instructions are enabled after a `do` and inside `if` blocks whose
+    /// condition is not zero. Calls which cannot be run are reported and
+    /// skipped, an `if` failing to evaluate opens a disabled block.
+    pub fn run(&self, calls: &[Call]) -> Execution {
+        let mut st = State {
+            total: 0,
+            executed: 0,
+            issues: Vec::new(),
+            enabled: true,
+            blocks: Vec::new(),
+        };
+
+        for c in calls {
+            self.exec(c, &mut st);
+        }
+
+        Execution {
+            total: st.total,
+            executed: st.executed,
+            issues: st.issues,
+        }
+    }
+}
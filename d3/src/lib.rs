@@ -0,0 +1,437 @@
+use std::fmt;
+
+use anyhow::{anyhow, Result};
+use common::{Answer, Puzzle};
+
+mod interp;
+mod trace;
+
+pub use interp::{Execution, Interpreter, Issue, IssueKind, OpFn, Opcode};
+pub use trace::{write_json, write_trace};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Instr {
+    Mul(u64, u64),
+    Do,
+    Dont,
+}
+
+/// Instruction found in the memory, `offset` is the byte of its first char
+/// and `len` its length in bytes
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Token {
+    pub offset: usize,
+    pub len: usize,
+    pub instr: Instr,
+}
+
+/// Number of digits accepted for each call operand
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Limits {
+    pub min_digits: usize,
+    pub max_digits: usize,
+}
+
+impl Default for Limits {
+    /// 1 to 3 digits as in the puzzle
+    fn default() -> Self {
+        Limits {
+            min_digits: 1,
+            max_digits: 3,
+        }
+    }
+}
+
+impl Limits {
+    pub fn validate(&self) -> Result<()> {
+        if (self.min_digits == 0) || (self.min_digits > self.max_digits) {
+            return Err(anyhow!(
+                "Invalid operand length {}..={}",
+                self.min_digits,
+                self.max_digits
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Why a `name(` did not give a call
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Rejection {
+    /// Operand of `len` digits, out of the limits
+    Digits { len: usize },
+    /// Operand within the limits but too large for a u64
+    Overflow,
+    /// Unexpected char at byte `at`
+    Syntax { at: usize },
+    /// Call given as argument deeper than `MAX_DEPTH`
+    Depth,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Rejected {
+    pub offset: usize,
+    pub reason: Rejection,
+}
+
+impl fmt::Display for Rejected {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "offset {}: ", self.offset)?;
+        match self.reason {
+            Rejection::Digits { len } => write!(f, "operand of {} digits", len),
+            Rejection::Overflow => write!(f, "operand overflows"),
+            Rejection::Syntax { at } => write!(f, "unexpected char at {}", at),
+            Rejection::Depth => write!(f, "calls nested deeper than {}", MAX_DEPTH),
+        }
+    }
+}
+
+/// Argument of a call, either a literal or a nested call
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Arg {
+    Num(u64),
+    Call(Call),
+}
+
+/// Any `name(args)` of the memory, `offset` and `len` locate it in bytes
+///
+/// The name is the longest run of letters, `_` and `'` before the
+/// parenthesis, instructions are then recognised on its end so that
+/// `xmul(2,4)` is a `mul`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Call {
+    pub offset: usize,
+    pub len: usize,
+    pub name: String,
+    pub args: Vec<Arg>,
+}
+
+impl Call {
+    /// Calls given as arguments
+    pub fn nested(&self) -> impl Iterator<Item = &Call> {
+        self.args.iter().filter_map(|a| match a {
+            Arg::Call(c) => Some(c),
+            Arg::Num(_) => None,
+        })
+    }
+
+    /// Puzzle instruction, from the end of the name to the parenthesis
+    fn instr(&self) -> Option<Token> {
+        let (suffix, instr) = match self.args.as_slice() {
+            [] if self.name.ends_with("don't") => ("don't", Instr::Dont),
+            [] if self.name.ends_with("do") => ("do", Instr::Do),
+            [Arg::Num(a), Arg::Num(b)] if self.name.ends_with("mul") => ("mul", Instr::Mul(*a, *b)),
+            _ => return None,
+        };
+
+        let skip = self.name.len() - suffix.len();
+
+        Some(Token {
+            offset: self.offset + skip,
+            len: self.len - skip,
+            instr,
+        })
+    }
+
+    /// Puzzle instructions of the call, its nested calls being looked at
+    /// when it is not one itself
+    fn tokens(&self, out: &mut Vec<Token>) {
+        match self.instr() {
+            Some(t) => out.push(t),
+            None => self.nested().for_each(|c| c.tokens(out)),
+        }
+    }
+}
+
+/// Calls of the memory with the `name(` which were dropped
+pub struct Scan {
+    pub calls: Vec<Call>,
+    pub rejected: Vec<Rejected>,
+}
+
+impl Scan {
+    /// `mul(a,b)`, `do()` and `don't()` in order, as in the puzzle
+    pub fn tokens(&self) -> Vec<Token> {
+        let mut ret = Vec::new();
+        for c in self.calls.iter() {
+            c.tokens(&mut ret);
+        }
+        ret
+    }
+}
+
+fn ident_char(c: u8) -> bool {
+    c.is_ascii_alphabetic() || (c == b'_') || (c == b'\'')
+}
+
+struct Scanner<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl Scanner<'_> {
+    /// Consume `s` if the input continues with it
+    fn eat(&mut self, s: &str) -> bool {
+        if self.data[self.pos..].starts_with(s.as_bytes()) {
+            self.pos += s.len();
+            return true;
+        }
+        false
+    }
+
+    fn skip_while(&mut self, f: fn(u8) -> bool) -> usize {
+        let start = self.pos;
+        while self.pos < self.data.len() && f(self.data[self.pos]) {
+            self.pos += 1;
+        }
+        start
+    }
+
+    fn peek(&self, f: fn(u8) -> bool) -> bool {
+        self.data.get(self.pos).is_some_and(|c| f(*c))
+    }
+
+    fn syntax(&self) -> Rejection {
+        Rejection::Syntax { at: self.pos }
+    }
+
+    fn operand(&mut self, limits: &Limits) -> Result<u64, Rejection> {
+        let start = self.skip_while(|c| c.is_ascii_digit());
+        let len = self.pos - start;
+
+        if (len < limits.min_digits) || (len > limits.max_digits) {
+            return Err(Rejection::Digits { len });
+        }
+
+        /* Only ASCII digits */
+        std::str::from_utf8(&self.data[start..self.pos])
+            .unwrap()
+            .parse()
+            .map_err(|_| Rejection::Overflow)
+    }
+
+    /// Name at the current position, with its parenthesis when it has one
+    fn name(&mut self) -> Option<(usize, String)> {
+        let offset = self.skip_while(ident_char);
+        let name = String::from_utf8_lossy(&self.data[offset..self.pos]).to_string();
+
+        self.eat("(").then_some((offset, name))
+    }
+}
+
+/// Calls nest at most this deep, a deeper one breaks the call around it
+pub const MAX_DEPTH: usize = 64;
+
+/// What may follow in a call being read
+#[derive(Clone, Copy)]
+enum Expect {
+    /// Right after the parenthesis, an argument or the end
+    First,
+    /// After an argument, a comma or the end
+    Next,
+    /// After a comma, an argument
+    Arg,
+}
+
+/// Call whose closing parenthesis is still to be read
+struct Open {
+    offset: usize,
+    name: String,
+    args: Vec<Arg>,
+    expect: Expect,
+}
+
+/// Every well formed `name(args)` of the memory, in order
+///
+/// Calls being read are kept on a stack. When one breaks it is reported,
+/// it and the calls around it are dropped and the calls they already
+/// contain are kept as calls of their own, the scan going on from the
+/// byte which broke it so that each byte is read once. Operands out of
+/// `limits` break their call.
+pub fn scan(data: &str, limits: &Limits) -> Scan {
+    let mut s = Scanner {
+        data: data.as_bytes(),
+        pos: 0,
+    };
+    let mut ret = Scan {
+        calls: Vec::new(),
+        rejected: Vec::new(),
+    };
+    let mut stack: Vec<Open> = Vec::new();
+
+    loop {
+        let Some(top) = stack.last_mut() else {
+            if s.pos >= s.data.len() {
+                break;
+            }
+            if !s.peek(ident_char) {
+                s.pos += 1;
+            } else if let Some((offset, name)) = s.name() {
+                stack.push(Open {
+                    offset,
+                    name,
+                    args: Vec::new(),
+                    expect: Expect::First,
+                });
+            }
+            continue;
+        };
+
+        let arg = matches!(top.expect, Expect::First | Expect::Arg);
+        let end = matches!(top.expect, Expect::First | Expect::Next);
+
+        let res = if end && s.eat(")") {
+            let open = stack.pop().unwrap();
+            let call = Call {
+                offset: open.offset,
+                len: s.pos - open.offset,
+                name: open.name,
+                args: open.args,
+            };
+
+            match stack.last_mut() {
+                Some(parent) => {
+                    parent.args.push(Arg::Call(call));
+                    parent.expect = Expect::Next;
+                }
+                None => ret.calls.push(call),
+            }
+            Ok(())
+        } else if !arg {
+            if s.eat(",") {
+                top.expect = Expect::Arg;
+                Ok(())
+            } else {
+                Err(s.syntax())
+            }
+        } else if s.peek(|c| c.is_ascii_digit()) {
+            s.operand(limits).map(|n| {
+                top.args.push(Arg::Num(n));
+                top.expect = Expect::Next;
+            })
+        } else if !s.peek(ident_char) {
+            Err(s.syntax())
+        } else if stack.len() >= MAX_DEPTH {
+            /* The name is read again as the start of a call */
+            Err(Rejection::Depth)
+        } else {
+            match s.name() {
+                Some((offset, name)) => {
+                    stack.push(Open {
+                        offset,
+                        name,
+                        args: Vec::new(),
+                        expect: Expect::First,
+                    });
+                    Ok(())
+                }
+                None => Err(s.syntax()),
+            }
+        };
+
+        if let Err(reason) = res {
+            let offset = stack.last().unwrap().offset;
+            ret.rejected.push(Rejected { offset, reason });
+
+            /* Outer calls first, their arguments come before the inner ones */
+            for open in stack.drain(..) {
+                ret.calls
+                    .extend(open.args.into_iter().filter_map(|a| match a {
+                        Arg::Call(c) => Some(c),
+                        Arg::Num(_) => None,
+                    }));
+            }
+        }
+    }
+
+    ret
+}
+
+/// Instructions of the memory with the puzzle operand limits
+pub fn tokenize(data: &str) -> Vec<Token> {
+    scan(data, &Limits::default()).tokens()
+}
+
+/// Sum of the products, all of them and only those enabled
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Totals {
+    pub all: u128,
+    pub enabled: u128,
+}
+
+/// Instruction as run, with the totals once it is done
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Step {
+    pub token: Token,
+    pub enabled: bool,
+    /// `None` for `do()` and `don't()`
+    pub product: Option<u128>,
+    pub totals: Totals,
+}
+
+/// Run the instructions one by one, products cannot overflow as u128
+///
+/// Only the sums can, they are then an error.
+pub fn steps(tokens: &[Token]) -> Result<Vec<Step>> {
+    let mut totals = Totals::default();
+    let mut enabled = true;
+    let mut ret = Vec::with_capacity(tokens.len());
+
+    for t in tokens {
+        let mut product = None;
+
+        match t.instr {
+            Instr::Mul(a, b) => {
+                let p = a as u128 * b as u128;
+                let overflow = || anyhow!("Sum overflows at offset {}", t.offset);
+
+                totals.all = totals.all.checked_add(p).ok_or_else(overflow)?;
+                if enabled {
+                    totals.enabled = totals.enabled.checked_add(p).ok_or_else(overflow)?;
+                }
+                product = Some(p);
+            }
+            Instr::Do => enabled = true,
+            Instr::Dont => enabled = false,
+        }
+
+        ret.push(Step {
+            token: *t,
+            enabled,
+            product,
+            totals,
+        });
+    }
+
+    Ok(ret)
+}
+
+pub fn run(tokens: &[Token]) -> Result<Totals> {
+    Ok(steps(tokens)?.last().map(|s| s.totals).unwrap_or_default())
+}
+
+fn answer(v: u128) -> Result<Answer> {
+    Answer::try_from(v).map_err(|_| anyhow!("{} does not fit an answer", v))
+}
+
+pub struct Day;
+
+impl Puzzle for Day {
+    type Input = Totals;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        run(&tokenize(input))
+    }
+
+    fn part1(t: &Self::Input) -> Result<Answer> {
+        answer(t.all)
+    }
+
+    fn part2(t: &Self::Input) -> Result<Answer> {
+        answer(t.enabled)
+    }
+}
+
+pub fn solve(input: &str) -> Result<(Answer, Answer)> {
+    common::solve::<Day>(input)
+}
@@ -1,52 +1,82 @@
-use std::fs::File;
-use std::io::{BufReader, Read};
-
 use anyhow::Result;
 use clap::Parser;
+use d3::{scan, steps, Interpreter, Limits};
 
 #[derive(Parser)]
 struct Args {
     file: String,
+    #[arg(long)]
+    /// Run the memory with add, sub, div and if/endif blocks
+    extended: bool,
+    #[arg(long, requires = "extended")]
+    /// Let extended instructions take nested calls as arguments
+    nested: bool,
+    #[arg(long, default_value_t = 1)]
+    /// Fewest digits of an operand
+    min_digits: usize,
+    #[arg(long, default_value_t = 3)]
+    /// Most digits of an operand
+    max_digits: usize,
+    #[arg(long)]
+    /// List the broken calls which were dropped, with the reason
+    rejected: bool,
+    #[arg(long, conflicts_with = "extended")]
+    /// Reprint the memory with the instructions highlighted
+    trace: bool,
+    #[arg(long, conflicts_with_all = ["trace", "extended"])]
+    /// Print every instruction as a JSON line
+    json: bool,
 }
 
-fn compute_over(data: &str) -> Result<u64> {
-    let mut tot = 0;
-    let re: regex::Regex = regex::Regex::new("mul\\(([0-9]+),([0-9]+)\\)")?;
+fn main() -> Result<()> {
+    let args = Args::parse();
+    let data = common::read_input(&args.file)?;
 
-    for m in re.captures_iter(data) {
-        println!("{:?}", m);
+    let limits = Limits {
+        min_digits: args.min_digits,
+        max_digits: args.max_digits,
+    };
+    limits.validate()?;
 
-        let first = m.get(1).unwrap().as_str().parse::<u64>().unwrap();
-        let second = m.get(2).unwrap().as_str().parse::<u64>().unwrap();
+    let s = scan(&data, &limits);
 
-        tot += first * second;
+    if args.rejected {
+        for r in s.rejected.iter() {
+            println!("Rejected {}", r);
+        }
     }
 
-    Ok(tot)
-}
+    if args.extended {
+        let mut interp = Interpreter::extended();
+        interp.allow_nested(args.nested);
 
-fn main() -> Result<()> {
-    let args = Args::parse();
-    let f = File::open(args.file)?;
-    let mut reader = BufReader::new(f);
-    let mut data = String::new();
-    reader.read_to_string(&mut data)?;
+        let exec = interp.run(&s.calls);
 
-    let tot = compute_over(&data)?;
-    println!("TOT is {}", tot);
+        for i in exec.issues.iter() {
+            eprintln!("{}", i);
+        }
 
-    let mut dont_tot = 0;
+        println!("Executed {} instructions", exec.executed);
+        println!("TOTAL is {}", exec.total);
 
-    /* Use a clear entry to outline the 'do' */
-    let data = data.clone().replace("do()", "£");
-    let re = regex::Regex::new("don\\'t\\(\\)[^£]*")?;
+        return Ok(());
+    }
+
+    let steps = steps(&s.tokens())?;
 
-    for m in re.find_iter(&data) {
-        dont_tot += compute_over(m.as_str())?;
+    if args.trace {
+        return d3::write_trace(&data, &steps, std::io::stdout().lock());
     }
 
-    println!("DONT TOT is {}", dont_tot);
-    println!("RES {}", tot - dont_tot);
+    if args.json {
+        return d3::write_json(&steps, std::io::stdout().lock());
+    }
+
+    let tot = steps.last().map(|s| s.totals).unwrap_or_default();
+
+    println!("TOT is {}", tot.all);
+    println!("DONT TOT is {}", tot.all - tot.enabled);
+    println!("RES {}", tot.enabled);
 
     Ok(())
 }
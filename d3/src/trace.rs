@@ -0,0 +1,83 @@
+use std::io::Write;
+
+use anyhow::Result;
+use serde::Serialize;
+
+use crate::{Instr, Step, Totals};
+
+const RESET: &str = "\x1b[0m";
+const BOLD: &str = "\x1b[1m";
+const GREEN: &str = "\x1b[32m";
+const RED: &str = "\x1b[31m";
+
+/// Reprint the memory with enabled `mul` in green, disabled ones in red and
+/// `do()`/`don't()` in bold
+///
+/// The margin holds the enabled and overall totals at the end of each line.
+pub fn write_trace<W: Write>(data: &str, steps: &[Step], mut out: W) -> Result<()> {
+    let mut next = steps.iter().peekable();
+    let mut totals = Totals::default();
+    let mut start = 0;
+
+    for line in data.split_inclusive('\n') {
+        let end = start + line.len();
+        let mut text = String::new();
+        let mut pos = start;
+
+        while let Some(s) = next.next_if(|s| s.token.offset < end) {
+            let t = s.token;
+            let style = match (t.instr, s.enabled) {
+                (Instr::Mul(..), true) => GREEN,
+                (Instr::Mul(..), false) => RED,
+                _ => BOLD,
+            };
+
+            text.push_str(&data[pos..t.offset]);
+            text.push_str(style);
+            text.push_str(&data[t.offset..t.offset + t.len]);
+            text.push_str(RESET);
+
+            pos = t.offset + t.len;
+            totals = s.totals;
+        }
+
+        text.push_str(data[pos..end].trim_end_matches(['\r', '\n']));
+
+        writeln!(out, "{:>12} {:>12} | {}", totals.enabled, totals.all, text)?;
+        start = end;
+    }
+
+    Ok(())
+}
+
+#[derive(Serialize)]
+struct JsonStep {
+    offset: usize,
+    instr: &'static str,
+    args: Vec<u64>,
+    enabled: bool,
+    product: Option<u128>,
+}
+
+/// One JSON object per instruction
+pub fn write_json<W: Write>(steps: &[Step], mut out: W) -> Result<()> {
+    for s in steps {
+        let (instr, args) = match s.token.instr {
+            Instr::Mul(a, b) => ("mul", vec![a, b]),
+            Instr::Do => ("do", vec![]),
+            Instr::Dont => ("don't", vec![]),
+        };
+
+        let j = JsonStep {
+            offset: s.token.offset,
+            instr,
+            args,
+            enabled: s.enabled,
+            product: s.product,
+        };
+
+        writeln!(out, "{}", serde_json::to_string(&j)?)?;
+    }
+
+    Ok(())
+}
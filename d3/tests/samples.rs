@@ -0,0 +1,354 @@
+use d3::{
+    run, scan, solve, steps, tokenize, write_json, write_trace, Call, Instr, Interpreter,
+    IssueKind, Limits, Opcode, Rejected, Rejection, MAX_DEPTH,
+};
+
+#[test]
+fn sample() {
+    let (p1, _) = solve(include_str!("../sample.txt")).unwrap();
+    assert_eq!(p1, 161);
+}
+
+#[test]
+fn sample2() {
+    let (p1, p2) = solve(include_str!("../sample2.txt")).unwrap();
+    assert_eq!(p1, 161);
+    assert_eq!(p2, 48);
+}
+
+#[test]
+fn expected() {
+    common::golden::check_expected(env!("CARGO_MANIFEST_DIR"), solve).unwrap();
+}
+
+#[test]
+fn tokens() {
+    let offsets: Vec<(usize, Instr)> = tokenize(include_str!("../sample2.txt"))
+        .iter()
+        .map(|t| (t.offset, t.instr))
+        .collect();
+    assert_eq!(
+        offsets,
+        vec![
+            (1, Instr::Mul(2, 4)),
+            (20, Instr::Dont),
+            (28, Instr::Mul(5, 5)),
+            (48, Instr::Mul(11, 8)),
+            (59, Instr::Do),
+            (64, Instr::Mul(8, 5)),
+        ]
+    );
+}
+
+#[test]
+fn broken_instructions() {
+    let instrs = |s: &str| -> Vec<Instr> { tokenize(s).iter().map(|t| t.instr).collect() };
+
+    assert_eq!(instrs("mul(mul(2,3)"), vec![Instr::Mul(2, 3)]);
+    assert_eq!(instrs("mul(1,mul(2,3))"), vec![Instr::Mul(2, 3)]);
+    assert_eq!(instrs("do(don't()do()"), vec![Instr::Dont, Instr::Do]);
+    assert_eq!(instrs("mul( 1,2)mul(1 ,2)mul(,2)mul(1,2"), vec![]);
+
+    /* The former implementation used this char as a marker */
+    let (p1, p2) = solve("£mul(2,3)don't()£mul(4,5)£do()£mul(1,1)").unwrap();
+    assert_eq!((p1, p2), (27, 7));
+}
+
+fn calls(data: &str) -> Vec<Call> {
+    scan(data, &Limits::default()).calls
+}
+
+fn nested() -> Interpreter {
+    let mut interp = Interpreter::extended();
+    interp.allow_nested(true);
+    interp
+}
+
+#[test]
+fn interpreter() {
+    let sample = calls(include_str!("../sample2.txt"));
+    let names: Vec<&str> = sample.iter().map(|c| c.name.as_str()).collect();
+    assert_eq!(names, vec!["xmul", "don't", "_mul", "mul", "undo", "mul"]);
+
+    let exec = Interpreter::puzzle().run(&sample);
+    assert_eq!(exec.total, 48);
+    assert!(exec.issues.is_empty());
+
+    let exec = nested().run(&calls(
+        "mul(add(1,2),sub(10,4))what()div(9,0)if(sub(2,2))mul(100,100)endif()if(1)div(7,2)endif()",
+    ));
+    assert_eq!(exec.total, 21);
+    assert_eq!(
+        exec.issues
+            .iter()
+            .map(|i| i.kind.clone())
+            .collect::<Vec<_>>(),
+        vec![
+            IssueKind::Unknown,
+            IssueKind::Failed("division by zero".to_string())
+        ]
+    );
+    assert_eq!(
+        exec.issues[0].to_string(),
+        "offset 23: unknown instruction 'what'"
+    );
+
+    /* Broken outer calls still give their nested ones */
+    let c = calls("mul(mul(2,3)x");
+    assert_eq!(c.len(), 1);
+    assert_eq!(c[0].offset, 4);
+
+    let exec = nested().run(&calls("mul(do(),2)endif()add(1)"));
+    assert_eq!(
+        exec.issues
+            .iter()
+            .map(|i| i.kind.clone())
+            .collect::<Vec<_>>(),
+        vec![
+            IssueKind::NotAValue,
+            IssueKind::Unmatched,
+            IssueKind::Arity {
+                expected: 2,
+                got: 1
+            }
+        ]
+    );
+}
+
+#[test]
+fn custom_instruction() {
+    let mut interp = nested();
+    interp.register(
+        "max",
+        Opcode::Value {
+            arity: 3,
+            f: |a| Ok(*a.iter().max().unwrap()),
+        },
+    );
+
+    let exec = interp.run(&calls("max(1,7,mul(2,2))don't()max(9,9,9)"));
+    assert_eq!(exec.total, 7);
+    assert_eq!(exec.executed, 3);
+}
+
+#[test]
+fn operand_limits() {
+    let s = scan("mul(1234,5)mul(12,345)mul(,1)", &Limits::default());
+    assert_eq!(s.tokens().len(), 1);
+    assert_eq!(s.tokens()[0].instr, Instr::Mul(12, 345));
+    assert_eq!(
+        s.rejected,
+        vec![
+            Rejected {
+                offset: 0,
+                reason: Rejection::Digits { len: 4 }
+            },
+            Rejected {
+                offset: 22,
+                reason: Rejection::Syntax { at: 26 }
+            }
+        ]
+    );
+
+    let s = scan("xmul(2,4)&mul(32,64]", &Limits::default());
+    assert_eq!(
+        s.rejected[0].to_string(),
+        "offset 10: unexpected char at 19"
+    );
+
+    let wide = Limits {
+        min_digits: 2,
+        max_digits: 25,
+    };
+    let s = scan(
+        "mul(1,2)mul(18446744073709551615,18446744073709551615)mul(99999999999999999999,2)",
+        &wide,
+    );
+    assert_eq!(s.tokens().len(), 1);
+    assert_eq!(
+        s.rejected.iter().map(|r| r.reason).collect::<Vec<_>>(),
+        vec![Rejection::Digits { len: 1 }, Rejection::Overflow]
+    );
+
+    /* The product only fits as u128 */
+    let tot = run(&s.tokens()).unwrap();
+    assert_eq!(tot.all, u64::MAX as u128 * u64::MAX as u128);
+    assert!(solve("mul(999,999)").is_ok());
+
+    /* Calls run by the interpreter follow the same limits */
+    let s = scan("mul(1234,5)add(1,22)", &Limits::default());
+    assert_eq!(s.rejected.len(), 1);
+    assert_eq!(Interpreter::extended().run(&s.calls).total, 23);
+
+    /* Only the call whose own operand is out of the limits is reported */
+    let s = scan("foo(mul(1234,5),mul(2,3))", &Limits::default());
+    assert_eq!(
+        s.rejected,
+        vec![Rejected {
+            offset: 4,
+            reason: Rejection::Digits { len: 4 }
+        }]
+    );
+    assert_eq!(s.tokens()[0].instr, Instr::Mul(2, 3));
+    assert_eq!(solve("foo(mul(1234,5),mul(2,3))").unwrap(), (6, 6));
+
+    let s = scan("add(1234,5)", &wide);
+    assert_eq!(
+        s.rejected.iter().map(|r| r.reason).collect::<Vec<_>>(),
+        vec![Rejection::Digits { len: 1 }]
+    );
+
+    assert!(Limits {
+        min_digits: 0,
+        max_digits: 3
+    }
+    .validate()
+    .is_err());
+}
+
+#[test]
+fn deep_nesting() {
+    /* Each byte is read once, however deep the broken calls */
+    let data = "mul(".repeat(60000) + "mul(2,3)";
+    assert_eq!(solve(&data).unwrap(), (6, 6));
+    let s = scan(&data, &Limits::default());
+    assert!(s
+        .rejected
+        .iter()
+        .all(|r| r.reason == Rejection::Depth || matches!(r.reason, Rejection::Syntax { .. })));
+
+    let s = scan(&"a(".repeat(200000), &Limits::default());
+    assert!(s.calls.is_empty());
+    assert_eq!(s.rejected.len(), 200000usize.div_ceil(MAX_DEPTH));
+
+    let deep = |n| "add(1,".repeat(n - 1) + "add(1,2)" + &")".repeat(n - 1);
+    assert_eq!(nested().run(&calls(&deep(MAX_DEPTH))).total, 66);
+
+    let s = scan(&deep(MAX_DEPTH + 1), &Limits::default());
+    assert_eq!(
+        s.rejected,
+        vec![Rejected {
+            offset: 6 * (MAX_DEPTH - 1),
+            reason: Rejection::Depth
+        }]
+    );
+    assert_eq!(s.calls.len(), 1);
+    assert_eq!(nested().run(&s.calls).total, 3);
+}
+
+#[test]
+fn trace() {
+    let steps = steps(&tokenize("mul(2,3)don't()\nmul(4,5)\nx\ndo()mul(1,1)")).unwrap();
+
+    let mut out = Vec::new();
+    write_trace(
+        "mul(2,3)don't()\nmul(4,5)\nx\ndo()mul(1,1)",
+        &steps,
+        &mut out,
+    )
+    .unwrap();
+    assert_eq!(
+        String::from_utf8(out).unwrap(),
+        "           6            6 | \x1b[32mmul(2,3)\x1b[0m\x1b[1mdon't()\x1b[0m\n\
+         \x20          6           26 | \x1b[31mmul(4,5)\x1b[0m\n\
+         \x20          6           26 | x\n\
+         \x20          7           27 | \x1b[1mdo()\x1b[0m\x1b[32mmul(1,1)\x1b[0m\n"
+    );
+
+    let mut out = Vec::new();
+    write_json(&steps[..2], &mut out).unwrap();
+    assert_eq!(
+        String::from_utf8(out).unwrap(),
+        "{\"offset\":0,\"instr\":\"mul\",\"args\":[2,3],\"enabled\":true,\"product\":6}\n\
+         {\"offset\":8,\"instr\":\"don't\",\"args\":[],\"enabled\":false,\"product\":null}\n"
+    );
+}
+
+/// Puzzle rules read literally, leftmost match first as a regex would
+fn reference(data: &str) -> (u64, u64) {
+    let b = data.as_bytes();
+    let (mut all, mut enabled, mut on) = (0, 0, true);
+    let mut p = 0;
+
+    let digits = |p: usize| {
+        (p..b.len().min(p + 4))
+            .take_while(|i| b[*i].is_ascii_digit())
+            .count()
+    };
+    let num = |p: usize, n: usize| data[p..p + n].parse::<u64>().unwrap();
+
+    while p < b.len() {
+        if b[p..].starts_with(b"do()") {
+            on = true;
+            p += 4;
+            continue;
+        }
+        if b[p..].starts_with(b"don't()") {
+            on = false;
+            p += 7;
+            continue;
+        }
+        if b[p..].starts_with(b"mul(") {
+            let n1 = digits(p + 4);
+            let c = p + 4 + n1;
+            if (1..=3).contains(&n1) && b.get(c) == Some(&b',') {
+                let n2 = digits(c + 1);
+                let e = c + 1 + n2;
+                if (1..=3).contains(&n2) && b.get(e) == Some(&b')') {
+                    let v = num(p + 4, n1) * num(c + 1, n2);
+                    all += v;
+                    if on {
+                        enabled += v;
+                    }
+                    p = e + 1;
+                    continue;
+                }
+            }
+        }
+        p += 1;
+    }
+
+    (all, enabled)
+}
+
+#[test]
+fn interpreter_matches_puzzle() {
+    let mut inputs: Vec<String> = [
+        include_str!("../sample.txt"),
+        include_str!("../sample2.txt"),
+        "from(mul(2,3))",
+        "mul(2,mul(3,4))",
+        "do(mul(2,3))don't(mul(4,5))mul(1,2)do()mul(mul(3,3),1)",
+        "select(,mul(5,5))what()mul(1234,5)mul(12,mul(1,1)",
+    ]
+    .iter()
+    .map(|s| s.to_string())
+    .collect();
+
+    let pieces = [
+        "mul(", "mul(1,2)", "mul(3,", "45)", "do()", "don't()", "do(", "from(", "1", "23", "4567",
+        ",", ")", "x", "'",
+    ];
+    let mut rand = common::lcg(3);
+    for _ in 0..500 {
+        inputs.push(
+            (0..30)
+                .map(|_| pieces[rand() as usize % pieces.len()])
+                .collect(),
+        );
+    }
+
+    for i in inputs.iter() {
+        assert_eq!(solve(i).unwrap(), reference(i), "{}", i);
+
+        let exec = Interpreter::puzzle().run(&calls(i));
+        assert_eq!(exec.total as u64, solve(i).unwrap().1, "{}", i);
+    }
+
+    /* Nesting is opt-in */
+    let exec = nested().run(&calls("mul(2,mul(3,4))"));
+    assert_eq!(exec.total, 24);
+    let exec = Interpreter::puzzle().run(&calls("mul(2,mul(3,4))"));
+    assert_eq!(exec.total, 12);
+    assert_eq!(exec.issues[0].kind, IssueKind::Nested);
+}
@@ -0,0 +1,17 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use d4::Xmap;
+
+fn xmas_at(c: &mut Criterion) {
+    let map = Xmap::new(include_str!("../sample.txt")).unwrap();
+
+    c.bench_function("Xmap::xmas_at", |b| {
+        b.iter(|| {
+            map.points()
+                .map(|p| map.xmas_at(black_box(p)))
+                .sum::<usize>()
+        })
+    });
+}
+
+criterion_group!(benches, xmas_at);
+criterion_main!(benches);
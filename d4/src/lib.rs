@@ -0,0 +1,86 @@
+use anyhow::Result;
+use common::{Answer, Puzzle};
+use grid::{Dir, Grid, Point};
+
+pub struct Xmap {
+    grid: Grid<char>,
+}
+
+impl Xmap {
+    pub fn new(data: &str) -> Result<Xmap> {
+        Ok(Xmap {
+            grid: Grid::parse(data, |c| c)?,
+        })
+    }
+
+    pub fn points(&self) -> impl Iterator<Item = Point> {
+        self.grid.points()
+    }
+
+    pub fn views_at(&self, p: Point) -> Vec<String> {
+        Dir::ALL
+            .iter()
+            .map(|d| {
+                self.grid
+                    .ray(p, *d)
+                    .take("XMAS".len())
+                    .map(|(_, c)| c)
+                    .collect()
+            })
+            .collect()
+    }
+
+    pub fn xmas_at(&self, p: Point) -> usize {
+        self.views_at(p).iter().filter(|v| **v == "XMAS").count()
+    }
+
+    pub fn xfind(&self, p: Point) -> bool {
+        /* a  b
+         e
+        c d */
+        let at = |t: Point| *self.grid.get(t).unwrap_or(&' ');
+
+        let a = at(p.step(Dir::NW));
+        let b = at(p.step(Dir::NE));
+        let c = at(p.step(Dir::SW));
+        let d = at(p.step(Dir::SE));
+
+        let e = at(p);
+
+        if e != 'A' {
+            return false;
+        }
+
+        let sa: String = [a, e, d].iter().collect();
+        let sb: String = [b, e, c].iter().collect();
+
+        if (["MAS", "SAM"].contains(&sa.as_str())) && (["MAS", "SAM"].contains(&sb.as_str())) {
+            return true;
+        }
+
+        false
+    }
+}
+
+pub struct Day;
+
+impl Puzzle for Day {
+    type Input = Xmap;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        Xmap::new(input)
+    }
+
+    fn part1(map: &Self::Input) -> Result<Answer> {
+        let xmas_cnt: usize = map.points().map(|p| map.xmas_at(p)).sum();
+        Ok(xmas_cnt as Answer)
+    }
+
+    fn part2(map: &Self::Input) -> Result<Answer> {
+        Ok(map.points().filter(|p| map.xfind(*p)).count() as Answer)
+    }
+}
+
+pub fn solve(input: &str) -> Result<(Answer, Answer)> {
+    common::solve::<Day>(input)
+}
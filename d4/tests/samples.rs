@@ -0,0 +1,13 @@
+use d4::solve;
+
+#[test]
+fn sample() {
+    let (p1, p2) = solve(include_str!("../sample.txt")).unwrap();
+    assert_eq!(p1, 18);
+    assert_eq!(p2, 9);
+}
+
+#[test]
+fn expected() {
+    common::golden::check_expected(env!("CARGO_MANIFEST_DIR"), solve).unwrap();
+}
@@ -0,0 +1,21 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use d5::load;
+
+fn reorder(c: &mut Criterion) {
+    let (rules, orders) = load(include_str!("../sample.txt")).unwrap();
+    let invalid: Vec<Vec<u64>> = orders
+        .into_iter()
+        .filter(|o| !rules.check_order(o))
+        .collect();
+
+    c.bench_function("OrderingRules::reorder", |b| {
+        b.iter(|| {
+            for o in invalid.iter() {
+                black_box(rules.reorder(black_box(o)).unwrap());
+            }
+        })
+    });
+}
+
+criterion_group!(benches, reorder);
+criterion_main!(benches);
@@ -0,0 +1,99 @@
+use std::collections::HashSet;
+use std::fmt::Write;
+
+use anyhow::Result;
+use clap::ValueEnum;
+use serde::Serialize;
+
+use crate::OrderingRules;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum GraphFormat {
+    Dot,
+    Json,
+}
+
+#[derive(Serialize)]
+struct GraphEdge {
+    before: u64,
+    after: u64,
+    line: usize,
+    /// The update has these pages the other way around
+    violated: bool,
+}
+
+#[derive(Serialize)]
+struct Graph {
+    nodes: Vec<u64>,
+    edges: Vec<GraphEdge>,
+}
+
+impl OrderingRules {
+    /// Rule graph, restricted to the pages of `update` when given
+    fn graph(&self, update: Option<&[u64]>) -> Graph {
+        let pruned = update.map(|u| self.prunning(u));
+        let rules = pruned.as_ref().unwrap_or(self);
+
+        let violated: HashSet<(u64, u64)> = update
+            .map(|u| self.violations(u))
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|v| v.rule)
+            .map(|r| (r.before, r.after))
+            .collect();
+
+        let mut nodes: Vec<u64> = rules.rules.keys().copied().collect();
+        if let Some(u) = update {
+            nodes.extend(u.iter().filter(|p| !rules.rules.contains_key(p)));
+        }
+        nodes.sort();
+
+        let mut seen: HashSet<(u64, u64)> = HashSet::new();
+        let edges = rules
+            .sources
+            .iter()
+            .filter(|r| seen.insert((r.before, r.after)))
+            .map(|r| GraphEdge {
+                before: r.before,
+                after: r.after,
+                line: r.line,
+                violated: violated.contains(&(r.before, r.after)),
+            })
+            .collect();
+
+        Graph { nodes, edges }
+    }
+
+    /// Render the rule graph, violating edges of `update` being highlighted
+    pub fn export(&self, format: GraphFormat, update: Option<&[u64]>) -> Result<String> {
+        let g = self.graph(update);
+
+        match format {
+            GraphFormat::Json => Ok(serde_json::to_string_pretty(&g)?),
+            GraphFormat::Dot => {
+                let mut ret = String::from("digraph rules {\n");
+
+                for n in g.nodes.iter() {
+                    writeln!(ret, "  {};", n)?;
+                }
+
+                for e in g.edges.iter() {
+                    let style = if e.violated {
+                        ", color=red, penwidth=2"
+                    } else {
+                        ""
+                    };
+                    writeln!(
+                        ret,
+                        "  {} -> {} [label=\"l{}\"{}];",
+                        e.before, e.after, e.line, style
+                    )?;
+                }
+
+                ret.push_str("}\n");
+
+                Ok(ret)
+            }
+        }
+    }
+}
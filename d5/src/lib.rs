@@ -0,0 +1,338 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use anyhow::{anyhow, Result};
+use common::{Answer, Puzzle};
+
+mod export;
+mod repair;
+mod stream;
+mod validate;
+
+pub use export::GraphFormat;
+pub use repair::{Edit, Repair, Strategy};
+pub use stream::{check_stream, StreamSummary};
+pub use validate::RuleIssue;
+
+#[derive(Clone, Debug)]
+pub struct OrderingRule {
+    pub id: u64,
+    pub predecessors: HashSet<u64>,
+    pub successors: HashSet<u64>,
+}
+
+impl OrderingRule {
+    pub fn new(id: u64) -> OrderingRule {
+        OrderingRule {
+            predecessors: HashSet::new(),
+            successors: HashSet::new(),
+            id,
+        }
+    }
+
+    fn prune(&mut self, ord: &[u64]) {
+        self.predecessors.retain(|v| ord.contains(v));
+        self.successors.retain(|v| ord.contains(v));
+    }
+}
+
+/// Pages whose rules form a cycle, they cannot be ordered
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CycleError {
+    pub pages: Vec<u64>,
+}
+
+impl fmt::Display for CycleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Cyclic rules between pages {:?}", self.pages)
+    }
+}
+
+impl std::error::Error for CycleError {}
+
+/// A `before|after` rule as read from the input
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RuleSource {
+    pub before: u64,
+    pub after: u64,
+    /// 1-based position of the rule in push order, which is its input line
+    pub line: usize,
+}
+
+/// Pair of pages of an update appearing in the wrong order
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Violation {
+    pub before: u64,
+    pub before_pos: usize,
+    pub after: u64,
+    pub after_pos: usize,
+    /// Rule requiring the opposite order, `None` when no rule orders them
+    pub rule: Option<RuleSource>,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} (pos {}) before {} (pos {})",
+            self.before, self.before_pos, self.after, self.after_pos
+        )?;
+
+        match self.rule {
+            Some(r) => write!(f, " breaks {}|{} line {}", r.before, r.after, r.line),
+            None => write!(f, " is not covered by any rule"),
+        }
+    }
+}
+
+pub struct OrderingRules {
+    rules: HashMap<u64, OrderingRule>,
+    sources: Vec<RuleSource>,
+}
+
+impl OrderingRules {
+    pub fn init() -> OrderingRules {
+        OrderingRules {
+            rules: HashMap::new(),
+            sources: Vec::new(),
+        }
+    }
+
+    pub fn push(&mut self, rule: &str) -> Result<()> {
+        let ab: Vec<u64> = rule.split('|').filter_map(|v| v.parse().ok()).collect();
+
+        if ab.len() != 2 {
+            return Err(anyhow!("{} ==> {:?} Should be 2 elems", rule, ab));
+        }
+
+        {
+            let a = self.rules.entry(ab[0]).or_insert(OrderingRule::new(ab[0]));
+            a.successors.insert(ab[1]);
+        }
+
+        {
+            let b = self.rules.entry(ab[1]).or_insert(OrderingRule::new(ab[1]));
+            b.predecessors.insert(ab[0]);
+        }
+
+        self.sources.push(RuleSource {
+            before: ab[0],
+            after: ab[1],
+            line: self.sources.len() + 1,
+        });
+
+        Ok(())
+    }
+
+    pub fn check_order(&self, vals: &[u64]) -> bool {
+        for (k, v) in vals.iter().enumerate() {
+            if let Some(cur) = self.rules.get(v) {
+                /* Check preds */
+                for pred in &vals[0..k] {
+                    if !cur.predecessors.contains(pred) {
+                        return false;
+                    }
+                }
+
+                for succs in &vals[k + 1..] {
+                    if !cur.successors.contains(succs) {
+                        return false;
+                    }
+                }
+            }
+        }
+
+        true
+    }
+
+    /// Every pair of pages which makes `check_order` reject the update
+    pub fn violations(&self, vals: &[u64]) -> Vec<Violation> {
+        let mut ret = Vec::new();
+
+        for (i, a) in vals.iter().enumerate() {
+            for (j, b) in vals.iter().enumerate().skip(i + 1) {
+                if self.ordered(*a, *b) {
+                    continue;
+                }
+
+                ret.push(Violation {
+                    before: *a,
+                    before_pos: i,
+                    after: *b,
+                    after_pos: j,
+                    rule: self.source(*b, *a),
+                });
+            }
+        }
+
+        ret
+    }
+
+    /// Whether `a` may come before `b`, pages without rules are free
+    fn ordered(&self, a: u64, b: u64) -> bool {
+        if !self.rules.contains_key(&a) && !self.rules.contains_key(&b) {
+            return true;
+        }
+
+        self.rules
+            .get(&a)
+            .is_some_and(|r| r.successors.contains(&b))
+    }
+
+    fn source(&self, before: u64, after: u64) -> Option<RuleSource> {
+        self.sources
+            .iter()
+            .find(|r| (r.before == before) && (r.after == after))
+            .copied()
+    }
+
+    pub fn prunning(&self, ord: &[u64]) -> OrderingRules {
+        let mut new_rules: HashMap<u64, OrderingRule> = ord
+            .iter()
+            .filter_map(|k| self.rules.get(k))
+            .map(|r| (r.id, r.clone()))
+            .collect();
+
+        for o in new_rules.values_mut() {
+            o.prune(ord);
+        }
+
+        let sources = self
+            .sources
+            .iter()
+            .filter(|r| ord.contains(&r.before) && ord.contains(&r.after))
+            .copied()
+            .collect();
+
+        OrderingRules {
+            rules: new_rules,
+            sources,
+        }
+    }
+
+    /// Order the pages of an update so that every rule is satisfied
+    ///
+    /// Kahn's algorithm over the rules restricted to the update, pages
+    /// becoming available are emitted in their original order. A repeated
+    /// page is emitted as many times as it appears, all copies together.
+    pub fn reorder(&self, vals: &[u64]) -> Result<Vec<u64>, CycleError> {
+        let pruned = self.prunning(vals);
+
+        let mut indeg: HashMap<u64, usize> = vals
+            .iter()
+            .map(|v| {
+                let d = pruned.rules.get(v).map(|r| r.predecessors.len());
+                (*v, d.unwrap_or(0))
+            })
+            .collect();
+
+        let mut copies: HashMap<u64, usize> = HashMap::new();
+        for v in vals {
+            *copies.entry(*v).or_default() += 1;
+        }
+
+        let mut ret: Vec<u64> = Vec::with_capacity(vals.len());
+
+        while let Some(next) = vals.iter().find(|v| indeg.get(v) == Some(&0)) {
+            indeg.remove(next);
+            ret.extend(std::iter::repeat_n(*next, copies[next]));
+
+            if let Some(r) = pruned.rules.get(next) {
+                for s in r.successors.iter() {
+                    if let Some(d) = indeg.get_mut(s) {
+                        *d -= 1;
+                    }
+                }
+            }
+        }
+
+        if indeg.is_empty() {
+            return Ok(ret);
+        }
+
+        Err(pruned.cycle_in(indeg.into_keys().collect()))
+    }
+
+    /// Narrow pages left by a topological sort to the ones on a cycle
+    ///
+    /// These are the strongly connected components of the pruned rules, and
+    /// the pages ordered with themselves.
+    fn cycle_in(&self, left: HashSet<u64>) -> CycleError {
+        let mut pages: Vec<u64> = self.components().into_iter().flatten().collect();
+
+        pages.extend(
+            left.into_iter()
+                .filter(|v| self.rules.get(v).is_some_and(|r| r.successors.contains(v))),
+        );
+        pages.sort();
+        pages.dedup();
+
+        CycleError { pages }
+    }
+}
+
+/// Parse a comma separated update
+pub fn parse_update(line: &str) -> Result<Vec<u64>> {
+    Ok(line
+        .trim()
+        .split(",")
+        .map(|v| v.trim().parse())
+        .collect::<Result<Vec<u64>, _>>()?)
+}
+
+/// Load rules up to the first blank line
+pub fn load_rules(input: &str) -> Result<OrderingRules> {
+    let mut rules = OrderingRules::init();
+
+    for l in input.lines().take_while(|l| !l.is_empty()) {
+        rules.push(l.trim())?;
+    }
+
+    Ok(rules)
+}
+
+/// Split the input on its first blank line into rules and updates
+pub fn load(input: &str) -> Result<(OrderingRules, Vec<Vec<u64>>)> {
+    let rules = load_rules(input)?;
+
+    let orders = input
+        .lines()
+        .skip_while(|l| !l.is_empty())
+        .filter(|l| !l.is_empty())
+        .map(parse_update)
+        .collect::<Result<Vec<Vec<u64>>>>()?;
+
+    Ok((rules, orders))
+}
+
+pub struct Day;
+
+impl Puzzle for Day {
+    type Input = (OrderingRules, Vec<Vec<u64>>);
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        load(input)
+    }
+
+    fn part1((rules, orders): &Self::Input) -> Result<Answer> {
+        Ok(orders
+            .iter()
+            .filter(|o| rules.check_order(o))
+            .map(|v| v[v.len() / 2])
+            .sum())
+    }
+
+    fn part2((rules, orders): &Self::Input) -> Result<Answer> {
+        let mut reordered_vecs: Vec<Vec<u64>> = Vec::new();
+
+        for i in orders.iter().filter(|o| !rules.check_order(o)) {
+            reordered_vecs.push(rules.reorder(i)?);
+        }
+
+        Ok(reordered_vecs.iter().map(|v| v[v.len() / 2]).sum())
+    }
+}
+
+pub fn solve(input: &str) -> Result<(Answer, Answer)> {
+    common::solve::<Day>(input)
+}
@@ -0,0 +1,155 @@
+use std::collections::HashSet;
+use std::fmt;
+
+use anyhow::{anyhow, Result};
+use clap::ValueEnum;
+
+use crate::OrderingRules;
+
+/// How an invalid update is fixed
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Strategy {
+    /// Move the fewest pages to other positions
+    Moves,
+    /// Drop the fewest pages
+    Removals,
+}
+
+/// One step of an edit script, positions are taken in the update as left
+/// by the previous steps
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Edit {
+    Move { page: u64, from: usize, to: usize },
+    Remove { page: u64, pos: usize },
+}
+
+impl fmt::Display for Edit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Edit::Move { page, from, to } => write!(f, "move {} from {} to {}", page, from, to),
+            Edit::Remove { page, pos } => write!(f, "remove {} at {}", page, pos),
+        }
+    }
+}
+
+pub struct Repair {
+    pub edits: Vec<Edit>,
+    pub result: Vec<u64>,
+}
+
+impl OrderingRules {
+    /// Longest subsequence of the update whose pages are already in order
+    ///
+    /// A page only extends a subsequence if it may come after every page of
+    /// it, so the result always passes `check_order`. It is the longest one
+    /// as long as the rules between the pages of the update are transitive.
+    fn kept(&self, vals: &[u64]) -> HashSet<usize> {
+        let mut best: Vec<usize> = vec![1; vals.len()];
+        let mut prev: Vec<Option<usize>> = vec![None; vals.len()];
+
+        for j in 0..vals.len() {
+            for i in 0..j {
+                if best[i] < best[j] {
+                    continue;
+                }
+
+                let mut chain = Some(i);
+                let mut fits = true;
+
+                while let Some(k) = chain {
+                    if !self.ordered(vals[k], vals[j]) {
+                        fits = false;
+                        break;
+                    }
+                    chain = prev[k];
+                }
+
+                if fits {
+                    best[j] = best[i] + 1;
+                    prev[j] = Some(i);
+                }
+            }
+        }
+
+        let mut ret = HashSet::new();
+        let mut cur = (0..vals.len()).max_by_key(|i| (best[*i], usize::MAX - i));
+
+        while let Some(i) = cur {
+            ret.insert(i);
+            cur = prev[i];
+        }
+
+        ret
+    }
+
+    /// Smallest edit script making the update pass `check_order`
+    pub fn repair(&self, vals: &[u64], strategy: Strategy) -> Result<Repair> {
+        let kept = self.kept(vals);
+
+        let ret = match strategy {
+            Strategy::Moves => self.repair_moves(vals, &kept)?,
+            Strategy::Removals => {
+                let mut edits = Vec::new();
+                let mut result = Vec::new();
+
+                for (i, v) in vals.iter().enumerate() {
+                    if kept.contains(&i) {
+                        result.push(*v);
+                    } else {
+                        edits.push(Edit::Remove {
+                            page: *v,
+                            pos: result.len(),
+                        });
+                    }
+                }
+
+                Repair { edits, result }
+            }
+        };
+
+        if !self.check_order(&ret.result) {
+            return Err(anyhow!(
+                "Rules do not order every page of {:?}, cannot repair it",
+                vals
+            ));
+        }
+
+        Ok(ret)
+    }
+
+    /// Move every page off the kept subsequence next to its predecessor in
+    /// the sorted update, the kept pages never move
+    fn repair_moves(&self, vals: &[u64], kept: &HashSet<usize>) -> Result<Repair> {
+        let target = self.reorder(vals)?;
+
+        let mut settled: HashSet<u64> = kept.iter().map(|i| vals[*i]).collect();
+        let mut cur = vals.to_vec();
+        let mut edits = Vec::new();
+
+        for (t, page) in target.iter().enumerate() {
+            if settled.contains(page) {
+                continue;
+            }
+
+            let from = cur.iter().position(|v| v == page).unwrap();
+            cur.remove(from);
+
+            let to = target[..t]
+                .iter()
+                .rev()
+                .find(|p| settled.contains(p))
+                .map(|p| cur.iter().position(|v| v == p).unwrap() + 1)
+                .unwrap_or(0);
+            cur.insert(to, *page);
+
+            settled.insert(*page);
+            edits.push(Edit::Move {
+                page: *page,
+                from,
+                to,
+            });
+        }
+
+        Ok(Repair { edits, result: cur })
+    }
+}
@@ -0,0 +1,67 @@
+use std::io::{BufRead, Write};
+
+use anyhow::{anyhow, Result};
+
+use crate::{parse_update, OrderingRules};
+
+/// Totals over a stream of updates, the sums being the two puzzle parts
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct StreamSummary {
+    pub valid: usize,
+    pub invalid: usize,
+    /// Invalid updates whose rules form a cycle, left out of `fixed_sum`
+    pub cyclic: usize,
+    pub sum: u64,
+    pub fixed_sum: u64,
+}
+
+/// Check updates one line at a time, writing a verdict per update
+///
+/// Each output line is `valid` or `invalid`, the middle page (after
+/// reordering for invalid ones) and the update as read. Updates which
+/// cannot be reordered give `cyclic`, the pages of the cycle and the
+/// update. Only the current update is kept in memory.
+pub fn check_stream<R: BufRead, W: Write>(
+    rules: &OrderingRules,
+    updates: R,
+    mut out: W,
+) -> Result<StreamSummary> {
+    let mut ret = StreamSummary::default();
+
+    for (n, line) in updates.lines().enumerate() {
+        let line = line?;
+        let line = line.trim();
+
+        if line.is_empty() {
+            continue;
+        }
+
+        let ord = parse_update(line).map_err(|e| anyhow!("line {}: {}", n + 1, e))?;
+
+        if rules.check_order(&ord) {
+            let mid = ord[ord.len() / 2];
+            ret.valid += 1;
+            ret.sum += mid;
+            writeln!(out, "valid {} {}", mid, line)?;
+        } else {
+            ret.invalid += 1;
+
+            match rules.reorder(&ord) {
+                Ok(fixed) => {
+                    let mid = fixed[fixed.len() / 2];
+                    ret.fixed_sum += mid;
+                    writeln!(out, "invalid {} {}", mid, line)?;
+                }
+                Err(e) => {
+                    let pages: Vec<String> = e.pages.iter().map(|p| p.to_string()).collect();
+                    ret.cyclic += 1;
+                    writeln!(out, "cyclic {} {}", pages.join(","), line)?;
+                }
+            }
+        }
+    }
+
+    out.flush()?;
+
+    Ok(ret)
+}
@@ -0,0 +1,207 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use crate::OrderingRules;
+
+/// Inconsistency found in a rule set, lines refer to the rule input
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RuleIssue {
+    /// `a|a`
+    SelfLoop { page: u64, line: usize },
+    /// Same rule given twice
+    Duplicate {
+        before: u64,
+        after: u64,
+        line: usize,
+        first_line: usize,
+    },
+    /// Both `a|b` and `b|a`
+    Contradiction {
+        a: u64,
+        b: u64,
+        line_ab: usize,
+        line_ba: usize,
+    },
+    /// Larger set of pages ordered in a circle
+    Cycle { pages: Vec<u64>, lines: Vec<usize> },
+}
+
+impl fmt::Display for RuleIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RuleIssue::SelfLoop { page, line } => {
+                write!(
+                    f,
+                    "line {}: {}|{} orders a page with itself",
+                    line, page, page
+                )
+            }
+            RuleIssue::Duplicate {
+                before,
+                after,
+                line,
+                first_line,
+            } => write!(
+                f,
+                "line {}: {}|{} duplicates line {}",
+                line, before, after, first_line
+            ),
+            RuleIssue::Contradiction {
+                a,
+                b,
+                line_ab,
+                line_ba,
+            } => write!(
+                f,
+                "lines {} and {}: {}|{} contradicts {}|{}",
+                line_ab, line_ba, a, b, b, a
+            ),
+            RuleIssue::Cycle { pages, lines } => {
+                write!(f, "lines {:?}: pages {:?} form a cycle", lines, pages)
+            }
+        }
+    }
+}
+
+impl OrderingRules {
+    /// Look for rules which cannot all be satisfied
+    ///
+    /// Issues are reported in rule order, cycles last. Pages caught in a
+    /// contradiction are not reported again as a cycle of two.
+    pub fn validate(&self) -> Vec<RuleIssue> {
+        let mut ret = Vec::new();
+
+        let mut seen: HashMap<(u64, u64), usize> = HashMap::new();
+
+        for r in self.sources.iter() {
+            if r.before == r.after {
+                ret.push(RuleIssue::SelfLoop {
+                    page: r.before,
+                    line: r.line,
+                });
+                continue;
+            }
+
+            if let Some(first_line) = seen.get(&(r.before, r.after)) {
+                ret.push(RuleIssue::Duplicate {
+                    before: r.before,
+                    after: r.after,
+                    line: r.line,
+                    first_line: *first_line,
+                });
+                continue;
+            }
+
+            if let Some(line_ab) = seen.get(&(r.after, r.before)) {
+                ret.push(RuleIssue::Contradiction {
+                    a: r.after,
+                    b: r.before,
+                    line_ab: *line_ab,
+                    line_ba: r.line,
+                });
+            }
+
+            seen.insert((r.before, r.after), r.line);
+        }
+
+        for pages in self.components() {
+            if pages.len() == 2 {
+                continue;
+            }
+
+            let lines = self
+                .sources
+                .iter()
+                .filter(|r| r.before != r.after)
+                .filter(|r| pages.contains(&r.before) && pages.contains(&r.after))
+                .map(|r| r.line)
+                .collect();
+
+            ret.push(RuleIssue::Cycle { pages, lines });
+        }
+
+        ret
+    }
+
+    /// Strongly connected components with more than one page (Tarjan)
+    ///
+    /// Iterative so that long rule chains do not exhaust the stack.
+    pub(crate) fn components(&self) -> Vec<Vec<u64>> {
+        let mut ids: Vec<u64> = self.rules.keys().copied().collect();
+        ids.sort();
+
+        let succs = |v: u64| -> Vec<u64> {
+            let mut s: Vec<u64> = self.rules[&v]
+                .successors
+                .iter()
+                .filter(|s| **s != v)
+                .copied()
+                .collect();
+            s.sort();
+            s
+        };
+
+        let mut index: HashMap<u64, usize> = HashMap::new();
+        let mut low: HashMap<u64, usize> = HashMap::new();
+        let mut on_stack: HashSet<u64> = HashSet::new();
+        let mut stack: Vec<u64> = Vec::new();
+        let mut ret = Vec::new();
+
+        for root in ids {
+            if index.contains_key(&root) {
+                continue;
+            }
+
+            let mut calls: Vec<(u64, Vec<u64>, usize)> = Vec::new();
+
+            index.insert(root, index.len());
+            low.insert(root, index[&root]);
+            stack.push(root);
+            on_stack.insert(root);
+            calls.push((root, succs(root), 0));
+
+            while let Some((v, next, i)) = calls.last_mut() {
+                let v = *v;
+
+                if let Some(w) = next.get(*i).copied() {
+                    *i += 1;
+
+                    if !index.contains_key(&w) {
+                        index.insert(w, index.len());
+                        low.insert(w, index[&w]);
+                        stack.push(w);
+                        on_stack.insert(w);
+                        calls.push((w, succs(w), 0));
+                    } else if on_stack.contains(&w) {
+                        low.insert(v, low[&v].min(index[&w]));
+                    }
+                    continue;
+                }
+
+                calls.pop();
+
+                if let Some((parent, _, _)) = calls.last() {
+                    low.insert(*parent, low[parent].min(low[&v]));
+                }
+
+                if low[&v] == index[&v] {
+                    let mut comp = Vec::new();
+                    while let Some(w) = stack.pop() {
+                        on_stack.remove(&w);
+                        comp.push(w);
+                        if w == v {
+                            break;
+                        }
+                    }
+
+                    if comp.len() > 1 {
+                        comp.sort();
+                        ret.push(comp);
+                    }
+                }
+            }
+        }
+
+        ret
+    }
+}
@@ -0,0 +1,235 @@
+use d5::{
+    check_stream, load, load_rules, solve, CycleError, Edit, GraphFormat, OrderingRules, RuleIssue,
+    RuleSource, Strategy,
+};
+
+#[test]
+fn sample() {
+    let (p1, p2) = solve(include_str!("../sample.txt")).unwrap();
+    assert_eq!(p1, 143);
+    assert_eq!(p2, 123);
+}
+
+#[test]
+fn expected() {
+    common::golden::check_expected(env!("CARGO_MANIFEST_DIR"), solve).unwrap();
+}
+
+#[test]
+fn reorder() {
+    let (rules, _) = load(include_str!("../sample.txt")).unwrap();
+
+    assert_eq!(
+        rules.reorder(&[75, 97, 47, 61, 53]),
+        Ok(vec![97, 75, 47, 61, 53])
+    );
+    assert_eq!(rules.reorder(&[61, 13, 29]), Ok(vec![61, 29, 13]));
+    assert_eq!(rules.reorder(&[75, 97, 75]), Ok(vec![97, 75, 75]));
+    assert_eq!(
+        rules.reorder(&[97, 13, 75, 29, 47]),
+        Ok(vec![97, 75, 47, 29, 13])
+    );
+}
+
+#[test]
+fn reorder_cycle() {
+    let mut rules = OrderingRules::init();
+    for r in ["1|2", "2|3", "3|1", "3|4", "5|1"] {
+        rules.push(r).unwrap();
+    }
+
+    assert_eq!(
+        rules.reorder(&[4, 3, 2, 1, 5]),
+        Err(CycleError {
+            pages: vec![1, 2, 3]
+        })
+    );
+}
+
+#[test]
+fn reorder_between_cycles() {
+    let mut rules = OrderingRules::init();
+    for r in ["1|2", "2|1", "2|3", "3|4", "4|5", "5|4", "6|6"] {
+        rules.push(r).unwrap();
+    }
+
+    assert_eq!(
+        rules.reorder(&[1, 2, 3, 4, 5]),
+        Err(CycleError {
+            pages: vec![1, 2, 4, 5]
+        })
+    );
+    assert_eq!(rules.reorder(&[3, 6]), Err(CycleError { pages: vec![6] }));
+}
+
+#[test]
+fn validate() {
+    let (rules, _) = load(include_str!("../sample.txt")).unwrap();
+    assert!(rules.validate().is_empty());
+
+    let mut rules = OrderingRules::init();
+    for r in ["1|2", "2|3", "3|4", "4|2", "5|5", "1|2", "6|7", "7|6"] {
+        rules.push(r).unwrap();
+    }
+
+    assert_eq!(
+        rules.validate(),
+        vec![
+            RuleIssue::SelfLoop { page: 5, line: 5 },
+            RuleIssue::Duplicate {
+                before: 1,
+                after: 2,
+                line: 6,
+                first_line: 1
+            },
+            RuleIssue::Contradiction {
+                a: 6,
+                b: 7,
+                line_ab: 7,
+                line_ba: 8
+            },
+            RuleIssue::Cycle {
+                pages: vec![2, 3, 4],
+                lines: vec![2, 3, 4]
+            },
+        ]
+    );
+}
+
+#[test]
+fn violations() {
+    let (rules, orders) = load(include_str!("../sample.txt")).unwrap();
+
+    for o in orders.iter() {
+        assert_eq!(rules.violations(o).is_empty(), rules.check_order(o));
+    }
+
+    let v = rules.violations(&[61, 13, 29]);
+    assert_eq!(v.len(), 1);
+    assert_eq!((v[0].before, v[0].before_pos), (13, 1));
+    assert_eq!((v[0].after, v[0].after_pos), (29, 2));
+    assert_eq!(
+        v[0].rule,
+        Some(RuleSource {
+            before: 29,
+            after: 13,
+            line: 8
+        })
+    );
+}
+
+#[test]
+fn repair() {
+    let (rules, orders) = load(include_str!("../sample.txt")).unwrap();
+
+    for o in orders.iter().filter(|o| !rules.check_order(o)) {
+        let r = rules.repair(o, Strategy::Moves).unwrap();
+        assert_eq!(r.result, rules.reorder(o).unwrap());
+
+        let r = rules.repair(o, Strategy::Removals).unwrap();
+        assert!(rules.check_order(&r.result));
+    }
+
+    /* 97 alone is misplaced */
+    let r = rules
+        .repair(&[75, 97, 47, 61, 53], Strategy::Moves)
+        .unwrap();
+    assert_eq!(
+        r.edits,
+        vec![Edit::Move {
+            page: 97,
+            from: 1,
+            to: 0
+        }]
+    );
+
+    /* 13 and 47 are both out of place */
+    let r = rules
+        .repair(&[97, 13, 75, 29, 47], Strategy::Moves)
+        .unwrap();
+    assert_eq!(r.edits.len(), 2);
+    assert_eq!(r.result, vec![97, 75, 47, 29, 13]);
+
+    let r = rules
+        .repair(&[97, 13, 75, 29, 47], Strategy::Removals)
+        .unwrap();
+    assert_eq!(r.edits.len(), 2);
+    assert_eq!(r.edits[0], Edit::Remove { page: 13, pos: 1 });
+    assert_eq!(r.result.len(), 3);
+
+    /* Rules are not transitive, 1 and 3 cannot be in the same update */
+    let mut rules = OrderingRules::init();
+    for r in ["1|2", "2|3"] {
+        rules.push(r).unwrap();
+    }
+    let r = rules.repair(&[1, 2, 3], Strategy::Removals).unwrap();
+    assert_eq!(r.edits.len(), 1);
+    assert!(rules.check_order(&r.result));
+    assert!(rules.repair(&[1, 2, 3], Strategy::Moves).is_err());
+}
+
+#[test]
+fn export_graph() {
+    let mut rules = OrderingRules::init();
+    for r in ["1|2", "2|3", "1|3", "3|4"] {
+        rules.push(r).unwrap();
+    }
+
+    let dot = rules.export(GraphFormat::Dot, Some(&[3, 1, 2])).unwrap();
+    assert_eq!(
+        dot,
+        "digraph rules {\n  1;\n  2;\n  3;\n  \
+         1 -> 2 [label=\"l1\"];\n  \
+         2 -> 3 [label=\"l2\", color=red, penwidth=2];\n  \
+         1 -> 3 [label=\"l3\", color=red, penwidth=2];\n}\n"
+    );
+
+    let json: serde_json::Value =
+        serde_json::from_str(&rules.export(GraphFormat::Json, None).unwrap()).unwrap();
+    assert_eq!(json["nodes"].as_array().unwrap().len(), 4);
+    assert_eq!(json["edges"].as_array().unwrap().len(), 4);
+    assert_eq!(json["edges"][3]["before"], 3);
+    assert_eq!(json["edges"][3]["violated"], false);
+}
+
+#[test]
+fn stream() {
+    let input = include_str!("../sample.txt");
+    let rules = load_rules(input).unwrap();
+    let updates: String = input
+        .lines()
+        .skip_while(|l| !l.is_empty())
+        .map(|l| format!("{}\n", l))
+        .collect();
+
+    let mut out: Vec<u8> = Vec::new();
+    let s = check_stream(&rules, updates.as_bytes(), &mut out).unwrap();
+
+    assert_eq!((s.valid, s.invalid), (3, 3));
+    assert_eq!((s.sum, s.fixed_sum), (143, 123));
+
+    let out = String::from_utf8(out).unwrap();
+    assert_eq!(out.lines().next(), Some("valid 61 75,47,61,53,29"));
+    assert_eq!(out.lines().nth(3), Some("invalid 47 75,97,47,61,53"));
+
+    let mut out: Vec<u8> = Vec::new();
+    check_stream(&rules, "47,75,97,47,47\n".as_bytes(), &mut out).unwrap();
+    assert_eq!(
+        String::from_utf8(out).unwrap(),
+        "invalid 47 47,75,97,47,47\n"
+    );
+
+    let mut cyclic = OrderingRules::init();
+    for r in ["1|2", "2|3", "3|1"] {
+        cyclic.push(r).unwrap();
+    }
+    let mut out: Vec<u8> = Vec::new();
+    let s = check_stream(&cyclic, "1,2,3\n1,3\n4,5\n".as_bytes(), &mut out).unwrap();
+    assert_eq!((s.valid, s.invalid, s.cyclic), (1, 2, 1));
+    assert_eq!(
+        String::from_utf8(out).unwrap(),
+        "cyclic 1,2,3 1,2,3\ninvalid 1 1,3\nvalid 5 4,5\n"
+    );
+
+    assert!(check_stream(&rules, "1,x\n".as_bytes(), Vec::new()).is_err());
+}
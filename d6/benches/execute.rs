@@ -0,0 +1,11 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use d6::Map;
+
+fn execute(c: &mut Criterion) {
+    let m = Map::load(include_str!("../sample.txt")).unwrap();
+
+    c.bench_function("Map::execute", |b| b.iter(|| black_box(&m).execute()));
+}
+
+criterion_group!(benches, execute);
+criterion_main!(benches);
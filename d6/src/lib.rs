@@ -0,0 +1,359 @@
+use std::collections::{HashMap, HashSet};
+
+use anyhow::{anyhow, Result};
+use clap::ValueEnum;
+use common::{Answer, Puzzle};
+use grid::{Dir, Grid, Point};
+use rayon::prelude::*;
+
+/// Result of a guard simulation
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Outcome {
+    /// The guard left the map, `steps` counts moves and `path` holds every
+    /// (position, direction) state from the start
+    Exit {
+        visited: usize,
+        steps: usize,
+        path: Vec<(Point, Dir)>,
+    },
+    /// The guard is stuck, states repeat from index `cycle_start` of `path`
+    /// every `cycle_len` states, `path` ending with one full cycle
+    Loop {
+        cycle_start: usize,
+        cycle_len: usize,
+        path: Vec<(Point, Dir)>,
+    },
+    NoGuard,
+}
+
+/// Position and heading of a guard
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Guard {
+    pub pos: Point,
+    pub dir: Dir,
+}
+
+impl Guard {
+    fn from_marker(pos: Point, c: char) -> Option<Guard> {
+        let dir = match c {
+            '^' => Dir::N,
+            '>' => Dir::E,
+            'v' => Dir::S,
+            '<' => Dir::W,
+            _ => return None,
+        };
+
+        Some(Guard { pos, dir })
+    }
+
+    fn ahead(&self) -> Point {
+        self.pos.step(self.dir)
+    }
+}
+
+/// How several guards share the map
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Mode {
+    /// Each guard walks alone
+    Independent,
+    /// Guards move in turn and block each other
+    Joint,
+}
+
+pub struct GuardReport {
+    pub start: Guard,
+    pub visited: usize,
+    pub outcome: Outcome,
+}
+
+pub struct Report {
+    pub guards: Vec<GuardReport>,
+    /// Cells visited by at least one guard
+    pub union: usize,
+}
+
+#[derive(Clone, Debug)]
+pub struct Map {
+    m: Grid<char>,
+}
+
+impl Map {
+    pub fn load(input: &str) -> Result<Map> {
+        let m = Grid::parse(input, |c| c)?;
+
+        for (p, c) in m.iter() {
+            if !"#.^>v<".contains(*c) {
+                return Err(anyhow!(
+                    "Unexpected value '{}' in map at ({}, {})",
+                    c,
+                    p.x,
+                    p.y
+                ));
+            }
+        }
+
+        Ok(Map { m })
+    }
+
+    pub fn points(&self) -> impl Iterator<Item = Point> {
+        self.m.points()
+    }
+
+    pub fn get(&self, p: Point) -> Option<char> {
+        self.m.get(p).copied()
+    }
+
+    /// Every guard in the map in row-major order
+    pub fn guards(&self) -> Vec<Guard> {
+        self.m
+            .iter()
+            .filter_map(|(p, c)| Guard::from_marker(p, *c))
+            .collect()
+    }
+
+    fn locate_guard(&self) -> Option<Guard> {
+        self.guards().first().copied()
+    }
+
+    #[allow(unused)]
+    pub fn print(m: &Grid<char>) {
+        print!("{}", m);
+    }
+
+    pub fn free_at(&mut self, p: Point) -> bool {
+        if let Some('#') = self.get(p) {
+            self.m[p] = '.';
+        }
+        false
+    }
+
+    pub fn obstacle_at(&mut self, p: Point) -> bool {
+        if let Some('.') = self.get(p) {
+            self.m[p] = '#';
+            return true;
+        }
+        false
+    }
+
+    fn blocked(&self, p: Point, extra: Option<Point>) -> bool {
+        (self.get(p) == Some('#')) || (extra == Some(p))
+    }
+
+    /// Walk the guard out of the map with an optional extra obstacle
+    ///
+    /// A loop is detected as soon as a (position, direction) state repeats.
+    fn walk(&self, start: Guard, extra: Option<Point>) -> (Outcome, HashSet<Point>) {
+        let Guard {
+            pos: mut g,
+            dir: mut w,
+        } = start;
+
+        let mut visited: HashSet<Point> = HashSet::from([g]);
+        let mut states: HashMap<(Point, Dir), usize> = HashMap::from([((g, w), 0)]);
+        let mut path: Vec<(Point, Dir)> = vec![(g, w)];
+        let mut steps = 0;
+
+        loop {
+            let targ = g.step(w);
+
+            if self.get(targ).is_none() {
+                let outcome = Outcome::Exit {
+                    visited: visited.len(),
+                    steps,
+                    path,
+                };
+                return (outcome, visited);
+            }
+
+            if self.blocked(targ, extra) {
+                /* We need to turn right */
+                w = w.turn_right();
+            } else {
+                /* Moving there */
+                g = targ;
+                visited.insert(g);
+                steps += 1;
+            }
+
+            if let Some(cycle_start) = states.get(&(g, w)) {
+                let outcome = Outcome::Loop {
+                    cycle_start: *cycle_start,
+                    cycle_len: path.len() - cycle_start,
+                    path,
+                };
+                return (outcome, visited);
+            }
+
+            states.insert((g, w), path.len());
+            path.push((g, w));
+        }
+    }
+
+    /// Simulate the first guard of the map
+    pub fn execute(&self) -> Outcome {
+        match self.locate_guard() {
+            Some(g) => self.walk(g, None).0,
+            None => Outcome::NoGuard,
+        }
+    }
+
+    /// Move all guards in turn, a guard ahead acting as an obstacle
+    ///
+    /// A loop is detected when the joint state of all remaining guards
+    /// repeats, every guard still in the map is then reported as looping.
+    fn walk_joint(&self, starts: &[Guard]) -> Vec<(Outcome, HashSet<Point>)> {
+        let mut guards: Vec<Option<Guard>> = starts.iter().copied().map(Some).collect();
+        let mut visited: Vec<HashSet<Point>> =
+            starts.iter().map(|g| HashSet::from([g.pos])).collect();
+        let mut paths: Vec<Vec<(Point, Dir)>> =
+            starts.iter().map(|g| vec![(g.pos, g.dir)]).collect();
+        let mut steps: Vec<usize> = vec![0; starts.len()];
+        let mut outcomes: Vec<Option<Outcome>> = vec![None; starts.len()];
+
+        let mut states: HashMap<Vec<Option<Guard>>, usize> = HashMap::from([(guards.clone(), 0)]);
+        let mut tick = 0;
+
+        while guards.iter().any(|g| g.is_some()) {
+            for i in 0..guards.len() {
+                let Some(g) = guards[i] else {
+                    continue;
+                };
+
+                let targ = g.ahead();
+
+                if self.get(targ).is_none() {
+                    outcomes[i] = Some(Outcome::Exit {
+                        visited: visited[i].len(),
+                        steps: steps[i],
+                        path: std::mem::take(&mut paths[i]),
+                    });
+                    guards[i] = None;
+                    continue;
+                }
+
+                let occupied = guards.iter().flatten().any(|o| o.pos == targ);
+
+                let next = if self.blocked(targ, None) || occupied {
+                    Guard {
+                        pos: g.pos,
+                        dir: g.dir.turn_right(),
+                    }
+                } else {
+                    visited[i].insert(targ);
+                    steps[i] += 1;
+                    Guard {
+                        pos: targ,
+                        dir: g.dir,
+                    }
+                };
+
+                paths[i].push((next.pos, next.dir));
+                guards[i] = Some(next);
+            }
+
+            tick += 1;
+
+            if let Some(cycle_start) = states.get(&guards) {
+                for (i, g) in guards.iter().enumerate() {
+                    if g.is_some() {
+                        /* Drop the repeated state as in `walk` */
+                        let mut path = std::mem::take(&mut paths[i]);
+                        path.pop();
+
+                        outcomes[i] = Some(Outcome::Loop {
+                            cycle_start: *cycle_start,
+                            cycle_len: tick - cycle_start,
+                            path,
+                        });
+                    }
+                }
+                break;
+            }
+
+            states.insert(guards.clone(), tick);
+        }
+
+        outcomes
+            .into_iter()
+            .map(|o| o.expect("Every guard has an outcome"))
+            .zip(visited)
+            .collect()
+    }
+
+    /// Simulate every guard of the map
+    pub fn execute_all(&self, mode: Mode) -> Report {
+        let starts = self.guards();
+
+        let walks = match mode {
+            Mode::Independent => starts.iter().map(|g| self.walk(*g, None)).collect(),
+            Mode::Joint => self.walk_joint(&starts),
+        };
+
+        let mut union: HashSet<Point> = HashSet::new();
+        let mut guards = Vec::new();
+
+        for (start, (outcome, visited)) in starts.into_iter().zip(walks) {
+            guards.push(GuardReport {
+                start,
+                visited: visited.len(),
+                outcome,
+            });
+            union.extend(visited);
+        }
+
+        Report {
+            guards,
+            union: union.len(),
+        }
+    }
+
+    /// Count the single obstacles which trap the guard in a loop
+    ///
+    /// Only cells on the original path can change the walk so they are the
+    /// only candidates, each one being simulated in parallel.
+    pub fn loop_obstacles(&self) -> Result<usize> {
+        let g = self.locate_guard().ok_or(anyhow!("No guard in map"))?;
+
+        let path = match self.walk(g, None).0 {
+            Outcome::Exit { path, .. } => path,
+            _ => return Err(anyhow!("Guard never leaves the map")),
+        };
+
+        let candidates: HashSet<Point> = path
+            .iter()
+            .map(|(p, _)| *p)
+            .filter(|p| *p != g.pos)
+            .collect();
+
+        Ok(candidates
+            .par_iter()
+            .filter(|p| matches!(self.walk(g, Some(**p)).0, Outcome::Loop { .. }))
+            .count())
+    }
+}
+
+pub struct Day;
+
+impl Puzzle for Day {
+    type Input = Map;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        Map::load(input)
+    }
+
+    fn part1(m: &Self::Input) -> Result<Answer> {
+        match m.execute() {
+            Outcome::Exit { visited, .. } => Ok(visited as Answer),
+            Outcome::Loop { .. } => Err(anyhow!("Guard never leaves the map")),
+            Outcome::NoGuard => Err(anyhow!("No guard in map")),
+        }
+    }
+
+    fn part2(m: &Self::Input) -> Result<Answer> {
+        Ok(m.loop_obstacles()? as Answer)
+    }
+}
+
+pub fn solve(input: &str) -> Result<(Answer, Answer)> {
+    common::solve::<Day>(input)
+}
@@ -0,0 +1,121 @@
+use d6::{solve, Guard, Map, Mode, Outcome};
+use grid::{Dir, Point};
+
+#[test]
+fn sample() {
+    let (p1, p2) = solve(include_str!("../sample.txt")).unwrap();
+    assert_eq!(p1, 41);
+    assert_eq!(p2, 6);
+}
+
+#[test]
+fn expected() {
+    common::golden::check_expected(env!("CARGO_MANIFEST_DIR"), solve).unwrap();
+}
+
+#[test]
+fn outcomes() {
+    let mut m = Map::load(include_str!("../sample.txt")).unwrap();
+
+    match m.execute() {
+        Outcome::Exit {
+            visited,
+            steps,
+            path,
+        } => {
+            assert_eq!(visited, 41);
+            assert_eq!(steps, 44);
+            assert_eq!(path.first(), Some(&(Point::new(4, 6), Dir::N)));
+        }
+        o => panic!("Unexpected {:?}", o),
+    }
+
+    assert!(m.obstacle_at(Point::new(3, 6)));
+    match m.execute() {
+        Outcome::Loop {
+            cycle_start,
+            cycle_len,
+            path,
+        } => {
+            assert!(cycle_len > 0);
+            assert_eq!(path.len(), cycle_start + cycle_len);
+            assert_eq!(path.first(), Some(&(Point::new(4, 6), Dir::N)));
+        }
+        o => panic!("Unexpected {:?}", o),
+    }
+
+    let m = Map::load("..#\n...\n").unwrap();
+    assert_eq!(m.execute(), Outcome::NoGuard);
+}
+
+#[test]
+fn orientations() {
+    for (map, dir) in [
+        ("...\n.>.\n...\n", Dir::E),
+        ("...\n.v.\n...\n", Dir::S),
+        ("...\n.<.\n...\n", Dir::W),
+    ] {
+        let m = Map::load(map).unwrap();
+        assert_eq!(
+            m.guards(),
+            vec![Guard {
+                pos: Point::new(1, 1),
+                dir
+            }]
+        );
+        assert!(matches!(m.execute(), Outcome::Exit { visited: 2, .. }));
+    }
+
+    assert!(Map::load("..x\n.^.\n").is_err());
+}
+
+#[test]
+fn several_guards() {
+    /* Both guards leave through the top row */
+    let m = Map::load("....\n^..^\n....\n").unwrap();
+
+    let r = m.execute_all(Mode::Independent);
+    assert_eq!(r.guards.len(), 2);
+    assert!(r.guards.iter().all(|g| g.visited == 2));
+    assert_eq!(r.union, 4);
+
+    /* Face to face */
+    let m = Map::load(">.<\n...\n").unwrap();
+
+    let r = m.execute_all(Mode::Independent);
+    assert_eq!(r.union, 3);
+
+    /* The second guard turns north then leaves, the first one turns south */
+    let r = m.execute_all(Mode::Joint);
+    assert!(r
+        .guards
+        .iter()
+        .all(|g| matches!(g.outcome, Outcome::Exit { .. })));
+    assert_eq!(r.guards[0].visited, 3);
+    assert_eq!(r.guards[1].visited, 1);
+    assert_eq!(r.union, 4);
+}
+
+#[test]
+fn joint_loop_paths() {
+    let m = Map::load(".#...\n....#\n.^...\n#..v.\n...#.\n").unwrap();
+    let r = m.execute_all(Mode::Joint);
+
+    for (g, (p, d)) in r
+        .guards
+        .iter()
+        .zip([(Point::new(1, 2), Dir::N), (Point::new(3, 3), Dir::S)])
+    {
+        match &g.outcome {
+            Outcome::Loop {
+                cycle_start,
+                cycle_len,
+                path,
+            } => {
+                assert_eq!(path.len(), cycle_start + cycle_len);
+                assert_eq!(path.first(), Some(&(p, d)));
+            }
+            o => panic!("Unexpected {:?}", o),
+        }
+    }
+}
@@ -0,0 +1,20 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use d8::AntMap;
+
+fn antinodes(c: &mut Criterion) {
+    let m = AntMap::new(include_str!("../sample.txt")).unwrap();
+    let ants = m.antenna_network();
+
+    for inline in [false, true] {
+        c.bench_function(&format!("AntMap::antinodes inline={}", inline), |b| {
+            b.iter(|| {
+                for a in ants.values() {
+                    black_box(m.antinodes(a, black_box(inline)));
+                }
+            })
+        });
+    }
+}
+
+criterion_group!(benches, antinodes);
+criterion_main!(benches);
@@ -0,0 +1,121 @@
+use std::collections::{HashMap, HashSet};
+
+use anyhow::Result;
+use common::{Answer, Puzzle};
+use grid::{Grid, Point};
+
+pub struct AntMap {
+    m: Grid<char>,
+}
+
+impl AntMap {
+    pub fn new(data: &str) -> Result<AntMap> {
+        Ok(AntMap {
+            m: Grid::parse(data, |c| c)?,
+        })
+    }
+
+    pub fn antenna_network(&self) -> HashMap<char, Vec<Point>> {
+        let mut ret: HashMap<char, Vec<Point>> = HashMap::new();
+
+        for (p, v) in self.m.iter() {
+            if *v != '.' {
+                ret.entry(*v).or_default().push(p);
+            }
+        }
+
+        ret
+    }
+
+    pub fn in_bound(&self, p: Point) -> bool {
+        self.m.contains(p)
+    }
+
+    pub fn antinodes(&self, coords: &[Point], inline: bool) -> HashSet<Point> {
+        let mut ret = HashSet::new();
+
+        for p1 in coords.iter() {
+            for p2 in coords.iter() {
+                let d = *p2 - *p1;
+
+                if p1 == p2 {
+                    continue;
+                }
+
+                if !inline {
+                    let t1 = *p1 - d;
+                    if self.in_bound(t1) {
+                        ret.insert(t1);
+                    }
+                    let t2 = *p2 + d;
+
+                    if self.in_bound(t2) {
+                        ret.insert(t2);
+                    }
+                } else {
+                    let mut t1 = *p1 - d;
+
+                    while self.in_bound(t1) {
+                        ret.insert(t1);
+                        t1 = t1 - d;
+                    }
+
+                    let mut t2 = *p1 + d;
+
+                    while self.in_bound(t2) {
+                        ret.insert(t2);
+                        t2 = t2 + d;
+                    }
+                }
+            }
+        }
+
+        ret
+    }
+
+    pub fn print(&self, anti: Option<&HashSet<Point>>) {
+        let mut m = self.m.clone();
+
+        if let Some(anti) = anti {
+            for p in anti {
+                m.set(*p, '#');
+            }
+        }
+
+        print!("{}", m);
+    }
+}
+
+/// Collect the antinodes of every antenna frequency
+pub fn all_antinodes(m: &AntMap, inline: bool) -> HashSet<Point> {
+    let mut anti: HashSet<Point> = HashSet::new();
+
+    for a in m.antenna_network().values() {
+        let lanti = m.antinodes(a, inline);
+        anti.extend(lanti);
+    }
+
+    anti
+}
+
+pub struct Day;
+
+impl Puzzle for Day {
+    type Input = AntMap;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        AntMap::new(input)
+    }
+
+    fn part1(m: &Self::Input) -> Result<Answer> {
+        Ok(all_antinodes(m, false).len() as Answer)
+    }
+
+    fn part2(m: &Self::Input) -> Result<Answer> {
+        Ok(all_antinodes(m, true).len() as Answer)
+    }
+}
+
+pub fn solve(input: &str) -> Result<(Answer, Answer)> {
+    common::solve::<Day>(input)
+}
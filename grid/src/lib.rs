@@ -0,0 +1,296 @@
+use std::fmt;
+use std::ops::{Index, IndexMut};
+
+use anyhow::{anyhow, Result};
+
+mod point;
+
+pub use point::{Dir, Point};
+
+/// Rectangular 2D grid stored row by row
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Grid<T> {
+    cells: Vec<T>,
+    w: usize,
+    h: usize,
+}
+
+impl<T: Clone> Grid<T> {
+    pub fn new(w: usize, h: usize, fill: T) -> Grid<T> {
+        Grid {
+            cells: vec![fill; w * h],
+            w,
+            h,
+        }
+    }
+}
+
+impl<T> Grid<T> {
+    /// Build a grid from rows which must all have the same length
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Result<Grid<T>> {
+        let h = rows.len();
+        let w = rows.first().map(|r| r.len()).unwrap_or(0);
+
+        if w == 0 {
+            return Err(anyhow!("Grid cannot be empty"));
+        }
+
+        let mut cells = Vec::with_capacity(w * h);
+
+        for (y, r) in rows.into_iter().enumerate() {
+            if r.len() != w {
+                return Err(anyhow!("Row {} has {} cells expected {}", y, r.len(), w));
+            }
+            cells.extend(r);
+        }
+
+        Ok(Grid { cells, w, h })
+    }
+
+    /// Parse one row per non-empty line, mapping each char to a cell
+    pub fn parse<F>(input: &str, f: F) -> Result<Grid<T>>
+    where
+        F: Fn(char) -> T,
+    {
+        let rows = input
+            .lines()
+            .map(|l| l.trim_end())
+            .filter(|l| !l.is_empty())
+            .map(|l| l.chars().map(&f).collect())
+            .collect();
+
+        Grid::from_rows(rows)
+    }
+
+    pub fn w(&self) -> usize {
+        self.w
+    }
+
+    pub fn h(&self) -> usize {
+        self.h
+    }
+
+    pub fn contains(&self, p: Point) -> bool {
+        (0 <= p.x) && (p.x < self.w as i64) && (0 <= p.y) && (p.y < self.h as i64)
+    }
+
+    fn offset(&self, p: Point) -> Option<usize> {
+        if self.contains(p) {
+            Some(p.y as usize * self.w + p.x as usize)
+        } else {
+            None
+        }
+    }
+
+    /// Checked access, `None` when out of the grid
+    pub fn get(&self, p: Point) -> Option<&T> {
+        self.offset(p).map(|o| &self.cells[o])
+    }
+
+    pub fn get_mut(&mut self, p: Point) -> Option<&mut T> {
+        self.offset(p).map(|o| &mut self.cells[o])
+    }
+
+    /// Store a value, returns false when out of the grid
+    pub fn set(&mut self, p: Point, val: T) -> bool {
+        if let Some(v) = self.get_mut(p) {
+            *v = val;
+            return true;
+        }
+        false
+    }
+
+    /// Every coordinate in row-major order
+    pub fn points(&self) -> impl Iterator<Item = Point> {
+        let w = self.w as i64;
+        (0..self.h as i64).flat_map(move |y| (0..w).map(move |x| Point::new(x, y)))
+    }
+
+    /// Every cell with its coordinate in row-major order
+    pub fn iter(&self) -> impl Iterator<Item = (Point, &T)> {
+        self.points().zip(self.cells.iter())
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        self.cells.chunks(self.w)
+    }
+
+    pub fn row(&self, y: usize) -> &[T] {
+        &self.cells[y * self.w..(y + 1) * self.w]
+    }
+
+    pub fn column(&self, x: usize) -> impl Iterator<Item = &T> {
+        self.cells.iter().skip(x).step_by(self.w)
+    }
+
+    /// Cells from `start` (included) walking towards `d` until leaving the grid
+    pub fn ray(&self, start: Point, d: Dir) -> impl Iterator<Item = (Point, &T)> {
+        let mut cur = start;
+        std::iter::from_fn(move || {
+            let v = self.get(cur)?;
+            let ret = (cur, v);
+            cur = cur.step(d);
+            Some(ret)
+        })
+    }
+
+    /// Both diagonals going through `p`, from their top end down
+    pub fn diagonals(&self, p: Point) -> [Vec<(Point, &T)>; 2] {
+        let mut desc = p;
+        while self.contains(desc.step(Dir::NW)) {
+            desc = desc.step(Dir::NW);
+        }
+
+        let mut asc = p;
+        while self.contains(asc.step(Dir::NE)) {
+            asc = asc.step(Dir::NE);
+        }
+
+        [
+            self.ray(desc, Dir::SE).collect(),
+            self.ray(asc, Dir::SW).collect(),
+        ]
+    }
+
+    fn neighbours_in<'a>(
+        &'a self,
+        p: Point,
+        dirs: &'a [Dir],
+    ) -> impl Iterator<Item = (Point, &'a T)> + 'a {
+        dirs.iter().filter_map(move |d| {
+            let n = p.step(*d);
+            self.get(n).map(|v| (n, v))
+        })
+    }
+
+    /// 4-connected neighbours inside the grid
+    pub fn neighbours4(&self, p: Point) -> impl Iterator<Item = (Point, &T)> {
+        self.neighbours_in(p, &Dir::CARDINALS)
+    }
+
+    /// 8-connected neighbours inside the grid
+    pub fn neighbours8(&self, p: Point) -> impl Iterator<Item = (Point, &T)> {
+        self.neighbours_in(p, &Dir::ALL)
+    }
+
+    /// First cell (row-major) matching the predicate
+    pub fn find<F>(&self, f: F) -> Option<Point>
+    where
+        F: Fn(&T) -> bool,
+    {
+        self.iter().find(|(_, v)| f(v)).map(|(p, _)| p)
+    }
+
+    /// All cells (row-major) matching the predicate
+    pub fn find_all<F>(&self, f: F) -> Vec<Point>
+    where
+        F: Fn(&T) -> bool,
+    {
+        self.iter().filter(|(_, v)| f(v)).map(|(p, _)| p).collect()
+    }
+}
+
+/// Unchecked access, panics when out of the grid
+impl<T> Index<Point> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, p: Point) -> &T {
+        self.get(p)
+            .unwrap_or_else(|| panic!("({}, {}) is out of the grid", p.x, p.y))
+    }
+}
+
+impl<T> IndexMut<Point> for Grid<T> {
+    fn index_mut(&mut self, p: Point) -> &mut T {
+        self.get_mut(p)
+            .unwrap_or_else(|| panic!("({}, {}) is out of the grid", p.x, p.y))
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for r in self.rows() {
+            for v in r {
+                write!(f, "{}", v)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SMALL: &str = "abc\ndef\n";
+
+    fn small() -> Grid<char> {
+        Grid::parse(SMALL, |c| c).unwrap()
+    }
+
+    #[test]
+    fn parse_and_access() {
+        let g = small();
+        assert_eq!(g.w(), 3);
+        assert_eq!(g.h(), 2);
+        assert_eq!(g.get(Point::new(2, 1)), Some(&'f'));
+        assert_eq!(g.get(Point::new(-1, 0)), None);
+        assert_eq!(g.get(Point::new(3, 0)), None);
+        assert_eq!(g[Point::new(1, 0)], 'b');
+    }
+
+    #[test]
+    fn ragged_rows_are_rejected() {
+        assert!(Grid::parse("ab\nc\n", |c| c).is_err());
+        assert!(Grid::<char>::parse("", |c| c).is_err());
+    }
+
+    #[test]
+    fn rows_and_columns() {
+        let g = small();
+        assert_eq!(g.row(1), &['d', 'e', 'f']);
+        assert_eq!(g.column(2).collect::<String>(), "cf");
+        assert_eq!(g.rows().count(), 2);
+    }
+
+    #[test]
+    fn rays_and_diagonals() {
+        let g = small();
+        let r: String = g.ray(Point::new(0, 0), Dir::SE).map(|(_, c)| c).collect();
+        assert_eq!(r, "ae");
+
+        let [desc, asc] = g.diagonals(Point::new(1, 1));
+        assert_eq!(desc.iter().map(|(_, c)| **c).collect::<String>(), "ae");
+        assert_eq!(asc.iter().map(|(_, c)| **c).collect::<String>(), "ce");
+    }
+
+    #[test]
+    fn neighbours() {
+        let g = small();
+        assert_eq!(g.neighbours4(Point::new(0, 0)).count(), 2);
+        assert_eq!(g.neighbours8(Point::new(0, 0)).count(), 3);
+        assert_eq!(g.neighbours8(Point::new(1, 1)).count(), 5);
+    }
+
+    #[test]
+    fn find_cells() {
+        let g = small();
+        assert_eq!(g.find(|c| *c == 'e'), Some(Point::new(1, 1)));
+        assert_eq!(g.find(|c| *c == 'z'), None);
+        assert_eq!(g.find_all(|c| *c > 'c').len(), 3);
+    }
+
+    #[test]
+    fn turns() {
+        assert_eq!(Dir::N.turn_right(), Dir::E);
+        assert_eq!(Dir::W.turn_right(), Dir::N);
+        assert_eq!(Dir::N.turn_left(), Dir::W);
+        assert_eq!(Dir::SE.opposite(), Dir::NW);
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!(small().to_string(), SMALL);
+    }
+}
@@ -0,0 +1,104 @@
+use std::ops::{Add, Mul, Sub};
+
+/// Signed grid coordinate, `y` grows downwards
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Point {
+    pub x: i64,
+    pub y: i64,
+}
+
+impl Point {
+    pub const fn new(x: i64, y: i64) -> Point {
+        Point { x, y }
+    }
+
+    /// Move by one cell in the given direction
+    pub fn step(self, d: Dir) -> Point {
+        self + d.delta()
+    }
+}
+
+impl Add for Point {
+    type Output = Point;
+
+    fn add(self, o: Point) -> Point {
+        Point::new(self.x + o.x, self.y + o.y)
+    }
+}
+
+impl Sub for Point {
+    type Output = Point;
+
+    fn sub(self, o: Point) -> Point {
+        Point::new(self.x - o.x, self.y - o.y)
+    }
+}
+
+impl Mul<i64> for Point {
+    type Output = Point;
+
+    fn mul(self, k: i64) -> Point {
+        Point::new(self.x * k, self.y * k)
+    }
+}
+
+/// Compass direction, `N` points to decreasing `y`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Dir {
+    N,
+    NE,
+    E,
+    SE,
+    S,
+    SW,
+    W,
+    NW,
+}
+
+impl Dir {
+    /// The 4-connected directions, clockwise from `N`
+    pub const CARDINALS: [Dir; 4] = [Dir::N, Dir::E, Dir::S, Dir::W];
+
+    /// The 8-connected directions, clockwise from `N`
+    pub const ALL: [Dir; 8] = [
+        Dir::N,
+        Dir::NE,
+        Dir::E,
+        Dir::SE,
+        Dir::S,
+        Dir::SW,
+        Dir::W,
+        Dir::NW,
+    ];
+
+    pub fn delta(self) -> Point {
+        match self {
+            Dir::N => Point::new(0, -1),
+            Dir::NE => Point::new(1, -1),
+            Dir::E => Point::new(1, 0),
+            Dir::SE => Point::new(1, 1),
+            Dir::S => Point::new(0, 1),
+            Dir::SW => Point::new(-1, 1),
+            Dir::W => Point::new(-1, 0),
+            Dir::NW => Point::new(-1, -1),
+        }
+    }
+
+    fn rotate(self, eighths: usize) -> Dir {
+        Dir::ALL[(self as usize + eighths) % 8]
+    }
+
+    /// Quarter turn clockwise
+    pub fn turn_right(self) -> Dir {
+        self.rotate(2)
+    }
+
+    /// Quarter turn counter-clockwise
+    pub fn turn_left(self) -> Dir {
+        self.rotate(6)
+    }
+
+    pub fn opposite(self) -> Dir {
+        self.rotate(4)
+    }
+}